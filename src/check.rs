@@ -0,0 +1,241 @@
+use crate::color::parse_color;
+use crate::config::{menu_path, Button, Layout};
+use crate::style::Style;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// A problem found while linting a layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Path to the offending button, e.g. "power > shutdown"
+    pub menu_path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.menu_path, self.message)
+    }
+}
+
+/// Check the settings, walk the whole button tree and report every
+/// semantic problem found.
+///
+/// Style defaults should not have been applied yet, so that each is
+/// reported once, in the `defaults` block that sets it.
+pub fn check_layout(layout: &Layout) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    // The same ranges a normal run rejects
+    if let Err((name, message)) = layout.settings.validate() {
+        issues.push(Issue {
+            menu_path: "settings".to_string(),
+            message: format!("{} {}", name, message),
+        });
+    }
+
+    check_colors(&layout.defaults, "defaults", &mut issues);
+
+    let mut labels = HashMap::new();
+    let mut menus = HashSet::new();
+    check_level(&layout.buttons, "", &mut labels, &mut menus, &mut issues);
+    issues
+}
+
+/// Report every color in `style` that cannot be parsed
+fn check_colors(style: &Style, menu_path: &str, issues: &mut Vec<Issue>) {
+    for (field, value) in style.colors() {
+        let Some(value) = value else {
+            continue;
+        };
+        if let Err(reason) = parse_color(value) {
+            issues.push(Issue {
+                menu_path: menu_path.to_string(),
+                message: format!("{} '{}' is not a valid color: {}", field, value, reason),
+            });
+        }
+    }
+}
+
+fn check_level(
+    buttons: &[Button],
+    parent_path: &str,
+    labels: &mut HashMap<String, String>,
+    menus: &mut HashSet<String>,
+    issues: &mut Vec<Issue>,
+) {
+    let mut keybinds: HashMap<char, String> = HashMap::new();
+
+    for button in buttons {
+        let path = menu_path(parent_path, &button.label);
+        let mut report = |message: String| {
+            issues.push(Issue {
+                menu_path: path.clone(),
+                message,
+            })
+        };
+
+        let has_action = !button.action.trim().is_empty();
+        if button.has_submenu() && has_action {
+            report("has both an action and a submenu; the action is never run".to_string());
+        } else if !button.has_submenu() && !has_action {
+            report("has neither an action nor a submenu".to_string());
+        }
+
+        // Labels identify buttons for styling and icon lookup, so they must
+        // be unique across the whole tree
+        match labels.get(&button.label) {
+            Some(other) => report(format!(
+                "label '{}' is also used by {}",
+                button.label, other
+            )),
+            None => {
+                labels.insert(button.label.clone(), path.clone());
+            }
+        }
+
+        // Keybinds only need to be unique within one menu level
+        if let Some(key) = button.keybind {
+            match keybinds.get(&key) {
+                Some(other) => report(format!("keybind '{}' is also used by {}", key, other)),
+                None => {
+                    keybinds.insert(key, path.clone());
+                }
+            }
+        }
+
+        if let Some(icon_path) = &button.icon_path {
+            if !Path::new(icon_path).is_file() {
+                report(format!("icon_path '{}' does not exist", icon_path));
+            }
+        }
+
+        if let Some(weight) = button.weight {
+            if !(weight > 0.0 && weight.is_finite()) {
+                report(format!("weight must be greater than 0, got {}", weight));
+            }
+        }
+
+        check_colors(&Style::of(button), &path, issues);
+        if let Some(defaults) = &button.defaults {
+            check_colors(defaults, &format!("{} defaults", path), issues);
+        }
+
+        // A named menu is linted once, at its first use, however many
+        // buttons open it
+        if let Some(name) = &button.expanded_from {
+            if !menus.insert(name.clone()) {
+                continue;
+            }
+        }
+        check_level(&button.children, &path, labels, menus, issues);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_layout;
+    use crate::format::LayoutFormat;
+    use crate::resolve::resolve_json;
+
+    #[test]
+    fn test_check_layout() {
+        let layout = parse_layout(
+            r##"[
+                {"label": "lock", "action": "hyprlock", "keybind": "l"},
+                {"label": "empty", "keybind": "l"},
+                {"label": "power", "action": "poweroff", "children": [
+                    {"label": "lock", "action": "swaylock", "keybind": "l"},
+                    {"label": "off", "action": "poweroff", "color": "#12345",
+                     "icon_path": "/nonexistent/off.png"},
+                    {"label": "reboot", "action": "reboot", "weight": 0}
+                ]}
+            ]"##,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+
+        let issues: Vec<String> = check_layout(&layout)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "empty: has neither an action nor a submenu",
+                "empty: keybind 'l' is also used by lock",
+                "power: has both an action and a submenu; the action is never run",
+                "power > lock: label 'lock' is also used by lock",
+                "power > off: icon_path '/nonexistent/off.png' does not exist",
+                "power > off: color '#12345' is not a valid color: expected 3, 4, 6 or 8 hex digits",
+                "power > reboot: weight must be greater than 0, got 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_shared_menu() {
+        let content = r#"{
+            "menus": {"power": [{"label": "off", "action": "poweroff"}]},
+            "buttons": [
+                {"label": "power", "submenu": "@power"},
+                {"label": "system", "submenu": "@power"}
+            ]
+        }"#;
+        let layout = resolve_json(content, parse_layout).unwrap();
+
+        assert_eq!(layout.buttons[1].children[0].label, "off");
+        assert_eq!(check_layout(&layout), []);
+    }
+
+    #[test]
+    fn test_check_defaults() {
+        let content = r#"{
+            "defaults": {"color": "blak"},
+            "buttons": [
+                {"label": "lock", "action": "hyprlock"},
+                {"label": "power", "defaults": {"foreground": "whte"}, "children": [
+                    {"label": "off", "action": "poweroff"},
+                    {"label": "reboot", "action": "reboot"}
+                ]}
+            ]
+        }"#;
+        let layout = resolve_json(content, parse_layout).unwrap();
+
+        // Each bad default is reported once, where it is set, rather than
+        // on every button it would apply to
+        let issues: Vec<String> = check_layout(&layout)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "defaults: color 'blak' is not a valid color: not a hex color, rgb(), hsl() or color name",
+                "power defaults: foreground 'whte' is not a valid color: not a hex color, rgb(), hsl() or color name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_settings() {
+        let layout = parse_layout(
+            r#"{"settings": {"wedge_alpha": 2}, "buttons": [{"label": "lock", "action": "hyprlock"}]}"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+
+        let issues: Vec<String> = check_layout(&layout)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            ["settings: wedge_alpha must be between 0 and 1, got 2"]
+        );
+    }
+}
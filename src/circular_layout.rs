@@ -1,283 +1,487 @@
-use std::cell::RefCell;
+use crate::menu_layout::{
+    cycle, draw_icon, draw_label, fill_button, paint_order, MenuButton, MenuLayout, Nav,
+    LABEL_PADDING,
+};
+use crate::settings::{Direction, Settings};
+use gtk::gdk::cairo::Context;
+use gtk::pango;
 use std::f64::consts::PI;
 
-// Animation configuration constants.
-const INNER_RADIUS_RATIO: f64 = 0.3;
-const ICON_SIZE: f64 = 48.0;
-const LABEL_OFFSET: f64 = 40.0;
-const LABEL_FONT_SIZE: f64 = 14.0;
-const LABEL_FONT_ALPHA: f64 = 0.9;
-const HOVER_Y_OFFSET: f64 = 8.0;
-const HOVER_SCALE: f64 = 1.12;
-const ANIMATION_SPEED: f64 = 0.01;
-
-// Thread-local animation state for smooth transitions.
-thread_local! {
-    static CURRENT_SCALE: RefCell<Vec<f64>> = RefCell::new(vec![1.0; 6]);
-    static CURRENT_Y_OFFSET: RefCell<Vec<f64>> = RefCell::new(vec![0.0; 6]);
+// Most of a wedge's angle the gap may take, so a narrow wedge still has
+// something left to draw and click.
+const MAX_GAP_SHARE: f64 = 0.5;
+
+/// One ring of wedges: the root menu, or a submenu expanded around it.
+///
+/// Angles are in radians as cairo measures them, from 3 o'clock clockwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ring {
+    pub inner_radius: f64,
+    pub outer_radius: f64,
+    /// Where the first wedge begins
+    pub start_angle: f64,
+    /// Angle the wedges span together, up to a full circle
+    pub sweep: f64,
+    pub direction: Direction,
 }
 
-/// Represents a single circular button wedge
-#[derive(Clone, Debug)]
-pub struct CircularButton {
-    pub label: String,
-    #[allow(dead_code)]
-    pub action: String,
-    pub color: (f64, f64, f64, f64), // RGBA
-    pub hover_color: (f64, f64, f64, f64),
-    #[allow(dead_code)]
-    pub icon_path: Option<String>, // Path to icon file
-    pub icon_char: Option<char>, // Custom icon character (Unicode/Nerd Font)
-    pub show_label: bool,        // Whether to show text label
+impl Ring {
+    /// The innermost ring, with outer radius `radius`, as the settings place it
+    pub fn root(radius: f64, settings: &Settings) -> Ring {
+        Ring {
+            inner_radius: radius * settings.inner_radius_ratio,
+            outer_radius: radius,
+            start_angle: (settings.start_angle - 90.0).to_radians(),
+            sweep: settings.sweep_angle.to_radians(),
+            direction: settings.direction,
+        }
+    }
+
+    /// The ring for a submenu opened from the wedge at `span`.
+    ///
+    /// It starts just outside this ring's hovered size and is as thick as
+    /// this ring, and is centered on the parent wedge. Its wedges get about
+    /// the arc length a wedge of the same weight has here, up to a full
+    /// circle. `weight` is the total weight of this ring's buttons and
+    /// `child_weight` that of the submenu's.
+    pub fn child(
+        &self,
+        span: (f64, f64),
+        weight: f64,
+        child_weight: f64,
+        settings: &Settings,
+    ) -> Ring {
+        let inner_radius = self.outer_radius * settings.hover_scale.max(1.0);
+        let outer_radius = inner_radius + self.outer_radius - self.inner_radius;
+
+        let unit = self.sweep / weight * (self.inner_radius + self.outer_radius)
+            / (inner_radius + outer_radius);
+        let sweep = (unit * child_weight).min(2.0 * PI);
+        let (start, end) = self.angles(span);
+        let mid = (start + end) / 2.0;
+
+        Ring {
+            inner_radius,
+            outer_radius,
+            start_angle: match self.direction {
+                Direction::Clockwise => mid - sweep / 2.0,
+                Direction::CounterClockwise => mid + sweep / 2.0,
+            },
+            sweep,
+            direction: self.direction,
+        }
+    }
+
+    /// The angle table shared by drawing and hit-testing: each wedge's extent
+    /// as radians travelled from the ring's start, in proportion to its weight
+    pub fn spans(&self, weights: &[f64]) -> Vec<(f64, f64)> {
+        let total: f64 = weights.iter().sum();
+        let mut from = 0.0;
+        weights
+            .iter()
+            .map(|weight| {
+                let to = from + self.sweep * weight / total;
+                let span = (from, to);
+                from = to;
+                span
+            })
+            .collect()
+    }
+
+    /// Start and end angle of a wedge's span, the start always being the
+    /// smaller so the wedge can be traced clockwise
+    fn angles(&self, (from, to): (f64, f64)) -> (f64, f64) {
+        match self.direction {
+            Direction::Clockwise => (self.start_angle + from, self.start_angle + to),
+            Direction::CounterClockwise => (self.start_angle - to, self.start_angle - from),
+        }
+    }
 }
 
-/// Calculate which wedge button the user clicked
-pub fn get_clicked_button(
-    x: f64,
-    y: f64,
-    center_x: f64,
-    center_y: f64,
-    radius: f64,
-    num_buttons: usize,
-    start_angle: f64,
-) -> i32 {
-    // Calculate distance from center
-    let dx = x - center_x;
-    let dy = y - center_y;
-    let distance = (dx * dx + dy * dy).sqrt();
-
-    // Check if click is within the donut ring
-    let inner_radius = radius * 0.4;
-    if distance > radius || distance < inner_radius {
-        return -1;
+/// The ring layout: wedges of a `Ring` around (center_x, center_y)
+pub struct RingLayout {
+    pub center_x: f64,
+    pub center_y: f64,
+    pub ring: Ring,
+    /// Whether this is the innermost ring, around the hub
+    pub hub: bool,
+    /// `Ring::spans` of the buttons, worked out once for every frame and
+    /// hit-test that uses this layout
+    pub spans: Vec<(f64, f64)>,
+}
+
+impl RingLayout {
+    /// The layout of buttons with `weights` on `ring`
+    pub fn new(center_x: f64, center_y: f64, ring: Ring, hub: bool, weights: &[f64]) -> Self {
+        Self {
+            center_x,
+            center_y,
+            ring,
+            hub,
+            spans: ring.spans(weights),
+        }
+    }
+
+    /// Start and end angle and outer radius of wedge `index`, grown by `scale`
+    fn wedge(&self, index: usize, scale: f64) -> (f64, f64, f64) {
+        let (start, end) = self.ring.angles(self.spans[index]);
+        (start, end, self.ring.outer_radius * scale.max(1.0))
     }
 
-    // Calculate angle from center (0 = right, π/2 = down, π = left, 3π/2 = up)
-    let mut angle = dy.atan2(dx);
+    /// Mid angle of each wedge
+    fn mid_angles(&self) -> impl Iterator<Item = f64> + '_ {
+        self.spans.iter().map(|&span| {
+            let (start, end) = self.ring.angles(span);
+            (start + end) / 2.0
+        })
+    }
+}
 
-    // Normalize angle to [0, 2π)
-    if angle < 0.0 {
-        angle += 2.0 * PI;
+impl MenuLayout for RingLayout {
+    /// A wedge; a hovered wedge grows outward while its inner edge stays put
+    fn button_path(&self, cr: &Context, index: usize, scale: f64, settings: &Settings) {
+        let (start_angle, end_angle, outer_radius) = self.wedge(index, scale);
+        wedge_path(
+            cr,
+            self.center_x,
+            self.center_y,
+            self.ring.inner_radius,
+            outer_radius,
+            start_angle,
+            end_angle,
+            settings,
+        );
     }
 
-    // Adjust for start_angle (which is -π/2, pointing up)
-    let mut relative_angle = angle - start_angle;
+    fn draw(
+        &self,
+        cr: &Context,
+        buttons: &[MenuButton],
+        hover_button: i32,
+        scales: &[f64],
+        settings: &Settings,
+    ) {
+        // Draw each button wedge
+        for i in paint_order(buttons.len(), hover_button) {
+            let scale = scales.get(i).copied().unwrap_or(1.0);
+            let (button_start, button_end, outer_radius) = self.wedge(i, scale);
+            self.button_path(cr, i, scale, settings);
+            draw_button_wedge(
+                cr,
+                self.center_x,
+                self.center_y,
+                self.ring.inner_radius,
+                outer_radius,
+                button_start,
+                button_end,
+                &buttons[i],
+                i as i32 == hover_button,
+                scale,
+                settings,
+            );
+        }
 
-    // Normalize to [0, 2π)
-    while relative_angle < 0.0 {
-        relative_angle += 2.0 * PI;
+        // The hub itself stays empty, optionally outlined
+        if self.hub && settings.hub_outline > 0.0 {
+            let (r, g, b, a) = settings.border_color().rgba(1.0);
+            cr.new_path();
+            cr.arc(
+                self.center_x,
+                self.center_y,
+                self.ring.inner_radius,
+                0.0,
+                2.0 * PI,
+            );
+            cr.set_line_width(settings.hub_outline);
+            cr.set_source_rgba(r, g, b, a);
+            let _ = cr.stroke();
+        }
     }
-    while relative_angle >= 2.0 * PI {
-        relative_angle -= 2.0 * PI;
+
+    /// Tab steps around the ring in button order; an arrow key picks the
+    /// wedge pointing most nearly that way
+    fn neighbor(&self, from: Option<usize>, nav: Nav, count: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let target = match nav {
+            Nav::Next | Nav::Previous => {
+                return Some(from.map_or(0, |from| cycle(from, nav, count)))
+            }
+            Nav::Right => 0.0,
+            Nav::Down => PI / 2.0,
+            Nav::Left => PI,
+            Nav::Up => -PI / 2.0,
+        };
+        let off_target = |angle: f64| {
+            let difference = (angle - target).rem_euclid(2.0 * PI);
+            difference.min(2.0 * PI - difference)
+        };
+        self.mid_angles()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| off_target(*a).total_cmp(&off_target(*b)))
+            .map(|(index, _)| index)
     }
+}
 
-    // Calculate which wedge this falls into
-    let wedge_size = (2.0 * PI) / num_buttons as f64;
-    let button_index = (relative_angle / wedge_size) as i32;
+/// Trace the outline of a wedge between `start_angle` and `end_angle`,
+/// leaving half of the configured gap empty on each side and rounding its
+/// corners. Drawing and hit-testing share this path.
+#[allow(clippy::too_many_arguments)]
+fn wedge_path(
+    cr: &Context,
+    center_x: f64,
+    center_y: f64,
+    inner_radius: f64,
+    outer_radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+    settings: &Settings,
+) {
+    let full_circle = end_angle - start_angle >= 2.0 * PI - f64::EPSILON;
+    let gap = if full_circle {
+        0.0
+    } else {
+        settings.wedge_gap.to_radians()
+    };
+    let (start, end) = inset(start_angle, end_angle, gap);
+    let half = (end - start) / 2.0;
+
+    // A lone wedge spans the whole ring and has no corners; otherwise the
+    // corners can be no rounder than the wedge is thick or wide
+    let mut corner = if full_circle {
+        0.0
+    } else {
+        settings
+            .corner_radius
+            .min((outer_radius - inner_radius) / 2.0)
+    };
+    if half < PI / 2.0 {
+        let sin = half.sin();
+        corner = corner.min(outer_radius * sin / (1.0 + sin));
+        if inner_radius > 0.0 {
+            corner = corner.min(inner_radius * sin / (1.0 - sin));
+        }
+    }
 
-    // Safety clamp
-    button_index.max(0).min(num_buttons as i32 - 1)
+    cr.new_path();
+    if corner <= 0.0 {
+        // Outer arc
+        cr.arc(center_x, center_y, outer_radius, start, end);
+        // Inner arc (reverse direction to close the path)
+        cr.arc_negative(center_x, center_y, inner_radius, end, start);
+        cr.close_path();
+        return;
+    }
+
+    // Each corner is a circle touching the wedge's straight edge and one of
+    // its arcs; these are the angles of the circles' centers
+    let outer = (corner / (outer_radius - corner)).asin();
+    let inner = (corner / (inner_radius + corner)).min(1.0).asin();
+    let at = |distance: f64, angle: f64| {
+        (
+            center_x + distance * angle.cos(),
+            center_y + distance * angle.sin(),
+        )
+    };
+
+    cr.arc(center_x, center_y, outer_radius, start + outer, end - outer);
+    let (x, y) = at(outer_radius - corner, end - outer);
+    cr.arc(x, y, corner, end - outer, end + PI / 2.0);
+    if inner_radius > 0.0 {
+        let (x, y) = at(inner_radius + corner, end - inner);
+        cr.arc(x, y, corner, end + PI / 2.0, end - inner + PI);
+        cr.arc_negative(center_x, center_y, inner_radius, end - inner, start + inner);
+        let (x, y) = at(inner_radius + corner, start + inner);
+        cr.arc(x, y, corner, start + inner + PI, start - PI / 2.0);
+    } else {
+        // Without a hub the edges meet in a point at the center
+        cr.line_to(center_x, center_y);
+    }
+    let (x, y) = at(outer_radius - corner, start + outer);
+    cr.arc(x, y, corner, start - PI / 2.0, start + outer);
+    cr.close_path();
+}
+
+/// Shrink the wedge between `start_angle` and `end_angle` by `gap`, half on
+/// each side, without letting the gap swallow the wedge
+fn inset(start_angle: f64, end_angle: f64, gap: f64) -> (f64, f64) {
+    let gap = gap.min((end_angle - start_angle) * MAX_GAP_SHARE);
+    (start_angle + gap / 2.0, end_angle - gap / 2.0)
 }
 
-/// Draw a single donut/ring slice with icon label
+/// Fill a single donut/ring slice, already traced as the current path, and
+/// draw its icon and label
+#[allow(clippy::too_many_arguments)]
 fn draw_button_wedge(
-    cr: &gtk::gdk::cairo::Context,
+    cr: &Context,
     center_x: f64,
     center_y: f64,
-    radius: f64,
+    inner_radius: f64,
+    scaled_radius: f64,
     start_angle: f64,
     end_angle: f64,
-    label: &str,
-    _icon_path: Option<&str>,
+    button: &MenuButton,
     is_hover: bool,
-    base_color: (f64, f64, f64, f64),
-    hover_color: (f64, f64, f64, f64),
     scale: f64,
-    icon_char: Option<char>,
-    show_label: bool,
+    settings: &Settings,
 ) {
     let mid_angle = (start_angle + end_angle) / 2.0;
-    let inner_radius = radius * INNER_RADIUS_RATIO;
-
-    // Calculate scaled outer radius (inner stays fixed)
-    let scaled_radius = if scale > 1.0 { radius * scale } else { radius };
-
-    // Draw donut ring slice without radial separators - just arcs
-    cr.new_path();
-    // Outer arc
-    cr.arc(center_x, center_y, scaled_radius, start_angle, end_angle);
-    // Inner arc (reverse direction to close the path)
-    cr.arc_negative(center_x, center_y, inner_radius, end_angle, start_angle);
-    cr.close_path();
 
     // Fill with color
-    let color = if is_hover { hover_color } else { base_color };
-    cr.set_source_rgba(color.0, color.1, color.2, color.3);
-    let _ = cr.fill();
-
-    // No border/outline
+    let foreground = fill_button(cr, button, is_hover);
 
     // Draw label text in the center of the button - use Nerd Font symbols
     let text_radius = (scaled_radius + inner_radius) / 2.0;
     let icon_x = center_x + text_radius * mid_angle.cos();
     let icon_y = center_y + text_radius * mid_angle.sin();
 
-    // Use custom icon_char if provided, otherwise use a generic default
-    let symbol_char = icon_char.unwrap_or_else(|| {
-        // If no custom icon is provided, use a generic bullet point
-        // Users should specify icon_char in their config for any button
-        log::debug!(
-            "No icon specified for label: '{}', using default bullet",
-            label
-        );
-        '•'
-    });
-
-    // Draw the icon - use FiraCode Nerd Font which is available
-    cr.select_font_face(
-        "FiraCode Nerd Font",
-        gtk::gdk::cairo::FontSlant::Normal,
-        gtk::gdk::cairo::FontWeight::Normal,
-    );
     // Scale the icon size based on hover state
-    let icon_size = ICON_SIZE * scale;
-    cr.set_font_size(icon_size);
-
-    // Get text extents for proper centering
-    let symbol_str = symbol_char.to_string();
-    match cr.text_extents(&symbol_str) {
-        Ok(extents) => {
-            let text_x = icon_x - extents.width() / 2.0;
-            let text_y = icon_y + extents.height() / 2.0;
-
-            cr.move_to(text_x, text_y);
-            cr.set_source_rgba(1.0, 1.0, 1.0, 1.0);
-            let _ = cr.show_text(&symbol_str);
-        }
-        Err(e) => {
-            log::warn!("Failed to render icon: {:?}", e);
-        }
-    }
+    draw_icon(
+        cr,
+        button,
+        icon_x,
+        icon_y,
+        button.icon_size * scale,
+        foreground,
+    );
 
     // Draw button label text below the icon (only if show_label is true)
-    if !show_label {
+    if !button.show_label {
         return;
     }
 
-    cr.select_font_face(
-        "Sans",
-        gtk::gdk::cairo::FontSlant::Normal,
-        gtk::gdk::cairo::FontWeight::Normal,
+    // Fit the label into the chord across the wedge at the icon's radius,
+    // ellipsizing anything longer
+    let half_angle = ((end_angle - start_angle) / 2.0).min(PI / 2.0);
+    let width = (2.0 * text_radius * half_angle.sin() - 2.0 * LABEL_PADDING).max(0.0);
+    draw_label(
+        cr,
+        button,
+        icon_x - width / 2.0,
+        icon_y + settings.label_offset,
+        width,
+        pango::Alignment::Center,
+        foreground,
+        settings,
     );
-    cr.set_font_size(LABEL_FONT_SIZE);
-    cr.set_source_rgba(1.0, 1.0, 1.0, LABEL_FONT_ALPHA);
+}
 
-    match cr.text_extents(label) {
-        Ok(label_extents) => {
-            let label_x = icon_x - label_extents.width() / 2.0;
-            let label_y = icon_y + LABEL_OFFSET;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            cr.move_to(label_x, label_y);
-            let _ = cr.show_text(label);
-        }
-        Err(e) => {
-            log::warn!("Failed to render label '{}': {:?}", label, e);
-        }
-    }
-}
-/// Draw the complete circular menu as a DONUT/RING with labels
-pub fn draw_circular_layout(
-    cr: &gtk::gdk::cairo::Context,
-    width: i32,
-    height: i32,
-    buttons: &[CircularButton],
-    hover_button: i32,
-) {
-    let width = width as f64;
-    let height = height as f64;
-    let center_x = width / 2.0;
-    let center_y = height / 2.0;
-    let radius = if width < height { width } else { height } * 0.35; // Increased to 35% for bigger ring
-    let start_angle = -PI / 2.0;
-    let wedge_size = (2.0 * PI) / buttons.len() as f64;
-
-    // Draw semi-opaque overlay for frosted/blur effect
-    cr.set_source_rgba(0.0, 0.0, 0.0, 0.35);
-    let _ = cr.paint();
-
-    // Update animation states smoothly using thread-local storage.
-    CURRENT_SCALE.with(|scale_cell| {
-        CURRENT_Y_OFFSET.with(|offset_cell| {
-            let mut scales = scale_cell.borrow_mut();
-            let mut offsets = offset_cell.borrow_mut();
-
-            // Ensure vectors are properly sized.
-            if scales.len() < buttons.len() {
-                scales.resize(buttons.len(), 1.0);
-            }
-            if offsets.len() < buttons.len() {
-                offsets.resize(buttons.len(), 0.0);
-            }
+    #[test]
+    fn test_spans() {
+        let ring = Ring::root(100.0, &Settings::default());
+        let spans = ring.spans(&[1.0, 2.0, 1.0]);
 
-            for (i, _button) in buttons.iter().enumerate() {
-                let target_scale = if i as i32 == hover_button {
-                    HOVER_SCALE
-                } else {
-                    1.0
-                };
-
-                if scales[i] < target_scale {
-                    scales[i] += ANIMATION_SPEED;
-                    if scales[i] > target_scale {
-                        scales[i] = target_scale;
-                    }
-                } else if scales[i] > target_scale {
-                    scales[i] -= ANIMATION_SPEED;
-                    if scales[i] < target_scale {
-                        scales[i] = target_scale;
-                    }
-                }
-
-                // Animate radial expansion
-                let target_y = if i as i32 == hover_button {
-                    HOVER_Y_OFFSET
-                } else {
-                    0.0
-                };
-                if (offsets[i] - target_y).abs() > 0.1 {
-                    offsets[i] += (target_y - offsets[i]) * 0.05;
-                } else {
-                    offsets[i] = target_y;
-                }
-            }
-        })
-    });
+        // The wedges follow each other and together fill the sweep
+        assert_eq!(spans[0].0, 0.0);
+        assert!(spans.windows(2).all(|pair| pair[0].1 == pair[1].0));
+        assert!((spans[2].1 - ring.sweep).abs() < 1e-9);
 
-    // Draw each button wedge
-    for (i, button) in buttons.iter().enumerate() {
-        let button_start = start_angle + (i as f64 * wedge_size);
-        let button_end = button_start + wedge_size;
-        let is_hover = i as i32 == hover_button;
+        let sizes: Vec<f64> = spans.iter().map(|(from, to)| to - from).collect();
+        assert!((sizes[0] - ring.sweep / 4.0).abs() < 1e-9);
+        assert!((sizes[1] - 2.0 * sizes[0]).abs() < 1e-9);
+        assert!((sizes[2] - sizes[0]).abs() < 1e-9);
+    }
 
-        let scale = CURRENT_SCALE.with(|cell| cell.borrow().get(i).copied().unwrap_or(1.0));
+    #[test]
+    fn test_angles_counter_clockwise() {
+        let clockwise = Ring {
+            inner_radius: 30.0,
+            outer_radius: 100.0,
+            start_angle: -PI / 2.0,
+            sweep: PI,
+            direction: Direction::Clockwise,
+        };
+        let counter_clockwise = Ring {
+            direction: Direction::CounterClockwise,
+            ..clockwise
+        };
+
+        // Counter-clockwise wedges are the clockwise ones mirrored about the
+        // start angle
+        for span in clockwise.spans(&[1.0, 3.0, 2.0]) {
+            let (start, end) = clockwise.angles(span);
+            let (mirrored_start, mirrored_end) = counter_clockwise.angles(span);
+            assert!((mirrored_start - (2.0 * clockwise.start_angle - end)).abs() < 1e-9);
+            assert!((mirrored_end - (2.0 * clockwise.start_angle - start)).abs() < 1e-9);
+            assert!(mirrored_start < mirrored_end);
+        }
+    }
 
-        draw_button_wedge(
-            cr,
-            center_x,
-            center_y,
-            radius,
-            button_start,
-            button_end,
-            &button.label,
-            button.icon_path.as_deref(),
-            is_hover,
-            button.color,
-            button.hover_color,
-            scale,
-            button.icon_char,
-            button.show_label,
-        );
+    #[test]
+    fn test_child() {
+        let settings = Settings::default();
+        let root = Ring::root(100.0, &settings);
+        let spans = root.spans(&[1.0, 2.0, 1.0]);
+
+        for direction in [Direction::Clockwise, Direction::CounterClockwise] {
+            let ring = Ring { direction, ..root };
+            let child = ring.child(spans[1], 4.0, 3.0, &settings);
+
+            // Outside the hovered parent and as thick as it
+            assert!(child.inner_radius >= ring.outer_radius * settings.hover_scale);
+            let thickness = |ring: &Ring| ring.outer_radius - ring.inner_radius;
+            assert!((thickness(&child) - thickness(&ring)).abs() < 1e-9);
+
+            // Centered on the parent wedge
+            let (start, end) = ring.angles(spans[1]);
+            let child_angles: Vec<(f64, f64)> = child
+                .spans(&[1.0; 3])
+                .into_iter()
+                .map(|span| child.angles(span))
+                .collect();
+            let child_start = child_angles
+                .iter()
+                .map(|&(start, _)| start)
+                .fold(f64::MAX, f64::min);
+            let child_end = child_angles
+                .iter()
+                .map(|&(_, end)| end)
+                .fold(f64::MIN, f64::max);
+            assert!(((child_start + child_end) / 2.0 - (start + end) / 2.0).abs() < 1e-9);
+        }
     }
 
-    // Inner circle is now just empty space (no outline or fill)
+    #[test]
+    fn test_neighbor() {
+        // Four wedges, the first centered at 12 o'clock
+        let ring = Ring {
+            inner_radius: 30.0,
+            outer_radius: 100.0,
+            start_angle: -0.75 * PI,
+            sweep: 2.0 * PI,
+            direction: Direction::Clockwise,
+        };
+        let layout = RingLayout::new(0.0, 0.0, ring, true, &[1.0; 4]);
+
+        assert_eq!(layout.neighbor(None, Nav::Next, 4), Some(0));
+        assert_eq!(layout.neighbor(None, Nav::Next, 0), None);
+
+        // Arrows pick the wedge pointing that way, wherever the selection is
+        assert_eq!(layout.neighbor(Some(2), Nav::Up, 4), Some(0));
+        assert_eq!(layout.neighbor(Some(0), Nav::Right, 4), Some(1));
+        assert_eq!(layout.neighbor(None, Nav::Down, 4), Some(2));
+        assert_eq!(layout.neighbor(Some(1), Nav::Left, 4), Some(3));
+
+        assert_eq!(layout.neighbor(Some(3), Nav::Next, 4), Some(0));
+        assert_eq!(layout.neighbor(Some(0), Nav::Previous, 4), Some(3));
+    }
+
+    #[test]
+    fn test_inset() {
+        let wedge = 45f64.to_radians();
+        let (start, end) = inset(0.0, wedge, 10f64.to_radians());
+        assert!((start - 5f64.to_radians()).abs() < 1e-9);
+        assert!((end - 40f64.to_radians()).abs() < 1e-9);
+
+        // A gap of 360°/n or more would leave nothing of n wedges
+        for gap in [45.0, 50.0, 89.0] {
+            let (start, end) = inset(0.0, wedge, f64::to_radians(gap));
+            assert!((end - start - wedge * (1.0 - MAX_GAP_SHARE)).abs() < 1e-9);
+        }
+    }
 }
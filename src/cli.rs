@@ -0,0 +1,168 @@
+use anyhow::{anyhow, bail, Result};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: snmenu [OPTIONS]
+
+Options:
+  -l, --layout <PATH>        Layout file to load (use '-' to read from stdin)
+      --layout-json <JSON>   Inline layout JSON instead of a layout file
+  -C, --css <PATH>           GTK stylesheet to load
+  -m, --merge                Merge the layout on top of the system-wide layout
+  -t, --theme <NAME|PATH>    Theme for @name colors (overrides settings.theme)
+      --check                Check the layout for problems and exit
+      --convert <PATH>       Write the layout to PATH in the format given by its
+                             extension (.json, .toml, .yaml) and exit
+  -h, --help                 Print this help and exit
+  -V, --version              Print version and exit
+
+Layouts ending in .toml, .yaml or .yml are read as TOML or YAML; anything
+else is read as JSON.
+
+Without --layout or --layout-json, the layout is taken from $SNMENU_CONFIG
+or searched for in $XDG_CONFIG_HOME/snmenu, $XDG_CONFIG_DIRS/snmenu and
+/etc/snmenu.";
+
+/// Where the layout should be read from
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutSource {
+    File(PathBuf),
+    Stdin,
+    Inline(String),
+}
+
+/// Options for running the menu
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    /// Explicit layout source; takes precedence over SNMENU_CONFIG and the search paths
+    pub layout: Option<LayoutSource>,
+    /// Explicit stylesheet; takes precedence over the search paths
+    pub css: Option<PathBuf>,
+    /// Merge the layout on top of the system-wide layout by label
+    pub merge: bool,
+    /// Theme name or path; takes precedence over the layout's `settings.theme`
+    pub theme: Option<String>,
+    /// Lint the layout instead of showing the menu
+    pub check: bool,
+    /// Convert the layout to this file instead of showing the menu
+    pub convert: Option<PathBuf>,
+}
+
+/// What the command line asked us to do
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+}
+
+/// Parse command-line arguments (excluding the program name)
+pub fn parse<I>(args: I) -> Result<Command>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Support both "--flag value" and "--flag=value"
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| -> Result<String> {
+            match inline_value {
+                Some(value) => Ok(value.to_string()),
+                None => args
+                    .next()
+                    .ok_or_else(|| anyhow!("option '{}' requires a value", name)),
+            }
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "-l" | "--layout" => {
+                let path = value(&flag)?;
+                let source = if path == "-" {
+                    LayoutSource::Stdin
+                } else {
+                    LayoutSource::File(PathBuf::from(path))
+                };
+                set_layout(&mut parsed, source)?;
+            }
+            "--layout-json" => {
+                let json = value(&flag)?;
+                set_layout(&mut parsed, LayoutSource::Inline(json))?;
+            }
+            "-C" | "--css" => parsed.css = Some(PathBuf::from(value(&flag)?)),
+            "-m" | "--merge" => parsed.merge = true,
+            "-t" | "--theme" => parsed.theme = Some(value(&flag)?),
+            "--check" => parsed.check = true,
+            "--convert" => parsed.convert = Some(PathBuf::from(value(&flag)?)),
+            _ => bail!("unrecognized argument '{}'", arg),
+        }
+    }
+
+    Ok(Command::Run(parsed))
+}
+
+fn set_layout(args: &mut Args, source: LayoutSource) -> Result<()> {
+    if args.layout.is_some() {
+        bail!("only one of --layout or --layout-json may be given");
+    }
+    args.layout = Some(source);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Command> {
+        parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        let command = parse_args(&["-l", "menu.json", "--css=style.css", "-t", "nord"]).unwrap();
+        assert_eq!(
+            command,
+            Command::Run(Args {
+                layout: Some(LayoutSource::File(PathBuf::from("menu.json"))),
+                css: Some(PathBuf::from("style.css")),
+                theme: Some("nord".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_stdin_and_inline() {
+        let command = parse_args(&["--layout", "-"]).unwrap();
+        assert_eq!(
+            command,
+            Command::Run(Args {
+                layout: Some(LayoutSource::Stdin),
+                ..Default::default()
+            })
+        );
+
+        let command = parse_args(&["--layout-json", "[]"]).unwrap();
+        assert_eq!(
+            command,
+            Command::Run(Args {
+                layout: Some(LayoutSource::Inline("[]".to_string())),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_args(&["-l"]).is_err());
+        assert!(parse_args(&["--bogus"]).is_err());
+        assert!(parse_args(&["-l", "a", "--layout-json", "[]"]).is_err());
+        assert_eq!(parse_args(&["-h", "--bogus"]).unwrap(), Command::Help);
+    }
+}
@@ -0,0 +1,535 @@
+//! CSS color syntax.
+//!
+//! Button colors accept everything a CSS color value does apart from
+//! `currentcolor` and the newer color spaces: `#RGB`, `#RGBA`, `#RRGGBB`,
+//! `#RRGGBBAA`, `rgb()`/`rgba()`, `hsl()`/`hsla()` and the named colors
+//! (including `transparent`). The leading `#` may be omitted from hex
+//! colors, as older layouts did.
+
+/// A parsed color with components in 0..=1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    /// Alpha written in the color itself, if any
+    pub alpha: Option<f64>,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+
+    const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            r: r as f64 / 255.0,
+            g: g as f64 / 255.0,
+            b: b as f64 / 255.0,
+            alpha: None,
+        }
+    }
+
+    /// RGBA tuple for cairo, using `default_alpha` unless the color sets its own
+    pub fn rgba(&self, default_alpha: f64) -> (f64, f64, f64, f64) {
+        (self.r, self.g, self.b, self.alpha.unwrap_or(default_alpha))
+    }
+
+    /// WCAG relative luminance
+    pub fn luminance(&self) -> f64 {
+        let [r, g, b] = [self.r, self.g, self.b].map(to_linear);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// WCAG contrast ratio between two opaque colors, from 1 to 21
+    pub fn contrast(&self, other: &Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// This color drawn with `alpha` over black, as wedges are over the overlay
+    pub fn over_black(&self, alpha: f64) -> Color {
+        Color {
+            r: self.r * alpha,
+            g: self.g * alpha,
+            b: self.b * alpha,
+            alpha: None,
+        }
+    }
+
+    /// A hover variant: lighter for dark colors and darker for light ones,
+    /// shifted in OKLab lightness so the change looks the same for every hue
+    pub fn hover_variant(&self) -> Color {
+        let [l, a, b] = self.to_oklab();
+        let l = if l < 0.6 {
+            l + HOVER_LIGHTNESS_STEP
+        } else {
+            l - HOVER_LIGHTNESS_STEP
+        };
+        Color {
+            alpha: self.alpha,
+            ..Color::from_oklab([l, a, b])
+        }
+    }
+
+    /// White or black, whichever reads better on this background.
+    ///
+    /// White is kept whenever it reaches `min_contrast`, matching the look
+    /// of dark wedges; otherwise the higher-contrast of the two wins.
+    pub fn foreground(&self, min_contrast: f64) -> Color {
+        let white = Color::rgb(255, 255, 255);
+        let black = Color::rgb(0, 0, 0);
+        let on_white = self.contrast(&white);
+        if on_white >= min_contrast || on_white >= self.contrast(&black) {
+            white
+        } else {
+            black
+        }
+    }
+
+    fn to_oklab(self) -> [f64; 3] {
+        let [r, g, b] = [self.r, self.g, self.b].map(to_linear);
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        [
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        ]
+    }
+
+    fn from_oklab([l, a, b]: [f64; 3]) -> Color {
+        let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+        let [r, g, b] = [
+            4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+            -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+            -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+        ]
+        .map(|c| from_linear(c).clamp(0.0, 1.0));
+        Color {
+            r,
+            g,
+            b,
+            alpha: None,
+        }
+    }
+}
+
+/// OKLab lightness change between a color and its derived hover color
+const HOVER_LIGHTNESS_STEP: f64 = 0.08;
+
+/// WCAG AA contrast for normal text
+pub const MIN_CONTRAST: f64 = 4.5;
+
+fn to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Wedge color used when a button sets none
+pub const DEFAULT_COLOR: Color = Color::rgb(0x81, 0xA1, 0xC1);
+/// Hover color used when a button sets none
+pub const DEFAULT_HOVER_COLOR: Color = Color::rgb(0x5E, 0x81, 0xAC);
+
+/// Parse a CSS color, describing what is wrong with it on failure
+pub fn parse_color(color_str: &str) -> Result<Color, String> {
+    let value = color_str.trim().to_ascii_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+    if let Some((name, args)) = value
+        .strip_suffix(')')
+        .and_then(|function| function.split_once('('))
+    {
+        return match name.trim() {
+            "rgb" | "rgba" => parse_rgb(args),
+            "hsl" | "hsla" => parse_hsl(args),
+            other => Err(format!("unknown color function '{}()'", other)),
+        };
+    }
+    if value == "transparent" {
+        return Ok(Color {
+            alpha: Some(0.0),
+            ..Color::rgb(0, 0, 0)
+        });
+    }
+    if let Some(&(_, color)) = NAMED_COLORS.iter().find(|(name, _)| *name == value) {
+        return Ok(color);
+    }
+    // Older layouts wrote six-digit hex colors without the '#'
+    if value.len() == 6 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return parse_hex(&value);
+    }
+
+    Err("not a hex color, rgb(), hsl() or color name".to_string())
+}
+
+fn parse_hex(hex: &str) -> Result<Color, String> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("hex colors may only contain 0-9 and a-f".to_string());
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).unwrap();
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+
+    let (r, g, b, a) = match hex.len() {
+        3 | 4 => (
+            digit(0) * 17,
+            digit(1) * 17,
+            digit(2) * 17,
+            (hex.len() == 4).then(|| digit(3) * 17),
+        ),
+        6 | 8 => (byte(0), byte(2), byte(4), (hex.len() == 8).then(|| byte(6))),
+        _ => return Err("expected 3, 4, 6 or 8 hex digits".to_string()),
+    };
+    Ok(Color {
+        alpha: a.map(|a| a as f64 / 255.0),
+        ..Color::rgb(r, g, b)
+    })
+}
+
+/// Split function arguments in either the legacy comma form `1, 2, 3, 0.5`
+/// or the modern space form `1 2 3 / 0.5`, returning the components and alpha
+fn split_args(args: &str) -> Result<(Vec<&str>, Option<&str>), String> {
+    let (components, alpha) = if args.contains(',') {
+        let mut parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = if parts.len() == 4 { parts.pop() } else { None };
+        (parts, alpha)
+    } else {
+        let (components, alpha) = match args.split_once('/') {
+            Some((components, alpha)) => (components, Some(alpha.trim())),
+            None => (args, None),
+        };
+        (components.split_whitespace().collect(), alpha)
+    };
+
+    if components.len() != 3 {
+        return Err(format!("expected 3 components, got {}", components.len()));
+    }
+    Ok((components, alpha))
+}
+
+/// Parse a number or percentage, where 100% equals `scale`
+fn parse_number(value: &str, scale: f64) -> Result<f64, String> {
+    let number = |s: &str| {
+        s.parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .ok_or_else(|| format!("'{}' is not a number", value))
+    };
+    match value.strip_suffix('%') {
+        Some(percent) => Ok(number(percent)? / 100.0 * scale),
+        None => number(value),
+    }
+}
+
+fn parse_alpha(alpha: Option<&str>) -> Result<Option<f64>, String> {
+    alpha
+        .map(|alpha| parse_number(alpha, 1.0).map(|a| a.clamp(0.0, 1.0)))
+        .transpose()
+}
+
+fn parse_rgb(args: &str) -> Result<Color, String> {
+    let (components, alpha) = split_args(args)?;
+    let mut channels = [0.0; 3];
+    for (channel, component) in channels.iter_mut().zip(&components) {
+        *channel = (parse_number(component, 255.0)? / 255.0).clamp(0.0, 1.0);
+    }
+    Ok(Color {
+        r: channels[0],
+        g: channels[1],
+        b: channels[2],
+        alpha: parse_alpha(alpha)?,
+    })
+}
+
+fn parse_hsl(args: &str) -> Result<Color, String> {
+    let (components, alpha) = split_args(args)?;
+    let hue = parse_hue(components[0])?;
+    let saturation = (parse_number(components[1], 1.0)?).clamp(0.0, 1.0);
+    let lightness = (parse_number(components[2], 1.0)?).clamp(0.0, 1.0);
+    if !components[1].ends_with('%') || !components[2].ends_with('%') {
+        return Err("hsl() saturation and lightness must be percentages".to_string());
+    }
+
+    // CSS Color 4, section 7.1
+    let channel = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    Ok(Color {
+        r: channel(0.0),
+        g: channel(8.0),
+        b: channel(4.0),
+        alpha: parse_alpha(alpha)?,
+    })
+}
+
+/// Parse a hue in degrees (the default), radians, gradians or turns
+fn parse_hue(value: &str) -> Result<f64, String> {
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f64::consts::PI),
+        ("turn", 360.0),
+    ];
+    let (number, factor) = units
+        .iter()
+        .find_map(|&(unit, factor)| value.strip_suffix(unit).map(|n| (n, factor)))
+        .unwrap_or((value, 1.0));
+    let degrees = parse_number(number, 1.0)? * factor;
+    Ok(degrees.rem_euclid(360.0))
+}
+
+/// The CSS named colors
+const NAMED_COLORS: &[(&str, Color)] = &[
+    ("aliceblue", Color::rgb(240, 248, 255)),
+    ("antiquewhite", Color::rgb(250, 235, 215)),
+    ("aqua", Color::rgb(0, 255, 255)),
+    ("aquamarine", Color::rgb(127, 255, 212)),
+    ("azure", Color::rgb(240, 255, 255)),
+    ("beige", Color::rgb(245, 245, 220)),
+    ("bisque", Color::rgb(255, 228, 196)),
+    ("black", Color::rgb(0, 0, 0)),
+    ("blanchedalmond", Color::rgb(255, 235, 205)),
+    ("blue", Color::rgb(0, 0, 255)),
+    ("blueviolet", Color::rgb(138, 43, 226)),
+    ("brown", Color::rgb(165, 42, 42)),
+    ("burlywood", Color::rgb(222, 184, 135)),
+    ("cadetblue", Color::rgb(95, 158, 160)),
+    ("chartreuse", Color::rgb(127, 255, 0)),
+    ("chocolate", Color::rgb(210, 105, 30)),
+    ("coral", Color::rgb(255, 127, 80)),
+    ("cornflowerblue", Color::rgb(100, 149, 237)),
+    ("cornsilk", Color::rgb(255, 248, 220)),
+    ("crimson", Color::rgb(220, 20, 60)),
+    ("cyan", Color::rgb(0, 255, 255)),
+    ("darkblue", Color::rgb(0, 0, 139)),
+    ("darkcyan", Color::rgb(0, 139, 139)),
+    ("darkgoldenrod", Color::rgb(184, 134, 11)),
+    ("darkgray", Color::rgb(169, 169, 169)),
+    ("darkgreen", Color::rgb(0, 100, 0)),
+    ("darkgrey", Color::rgb(169, 169, 169)),
+    ("darkkhaki", Color::rgb(189, 183, 107)),
+    ("darkmagenta", Color::rgb(139, 0, 139)),
+    ("darkolivegreen", Color::rgb(85, 107, 47)),
+    ("darkorange", Color::rgb(255, 140, 0)),
+    ("darkorchid", Color::rgb(153, 50, 204)),
+    ("darkred", Color::rgb(139, 0, 0)),
+    ("darksalmon", Color::rgb(233, 150, 122)),
+    ("darkseagreen", Color::rgb(143, 188, 143)),
+    ("darkslateblue", Color::rgb(72, 61, 139)),
+    ("darkslategray", Color::rgb(47, 79, 79)),
+    ("darkslategrey", Color::rgb(47, 79, 79)),
+    ("darkturquoise", Color::rgb(0, 206, 209)),
+    ("darkviolet", Color::rgb(148, 0, 211)),
+    ("deeppink", Color::rgb(255, 20, 147)),
+    ("deepskyblue", Color::rgb(0, 191, 255)),
+    ("dimgray", Color::rgb(105, 105, 105)),
+    ("dimgrey", Color::rgb(105, 105, 105)),
+    ("dodgerblue", Color::rgb(30, 144, 255)),
+    ("firebrick", Color::rgb(178, 34, 34)),
+    ("floralwhite", Color::rgb(255, 250, 240)),
+    ("forestgreen", Color::rgb(34, 139, 34)),
+    ("fuchsia", Color::rgb(255, 0, 255)),
+    ("gainsboro", Color::rgb(220, 220, 220)),
+    ("ghostwhite", Color::rgb(248, 248, 255)),
+    ("gold", Color::rgb(255, 215, 0)),
+    ("goldenrod", Color::rgb(218, 165, 32)),
+    ("gray", Color::rgb(128, 128, 128)),
+    ("green", Color::rgb(0, 128, 0)),
+    ("greenyellow", Color::rgb(173, 255, 47)),
+    ("grey", Color::rgb(128, 128, 128)),
+    ("honeydew", Color::rgb(240, 255, 240)),
+    ("hotpink", Color::rgb(255, 105, 180)),
+    ("indianred", Color::rgb(205, 92, 92)),
+    ("indigo", Color::rgb(75, 0, 130)),
+    ("ivory", Color::rgb(255, 255, 240)),
+    ("khaki", Color::rgb(240, 230, 140)),
+    ("lavender", Color::rgb(230, 230, 250)),
+    ("lavenderblush", Color::rgb(255, 240, 245)),
+    ("lawngreen", Color::rgb(124, 252, 0)),
+    ("lemonchiffon", Color::rgb(255, 250, 205)),
+    ("lightblue", Color::rgb(173, 216, 230)),
+    ("lightcoral", Color::rgb(240, 128, 128)),
+    ("lightcyan", Color::rgb(224, 255, 255)),
+    ("lightgoldenrodyellow", Color::rgb(250, 250, 210)),
+    ("lightgray", Color::rgb(211, 211, 211)),
+    ("lightgreen", Color::rgb(144, 238, 144)),
+    ("lightgrey", Color::rgb(211, 211, 211)),
+    ("lightpink", Color::rgb(255, 182, 193)),
+    ("lightsalmon", Color::rgb(255, 160, 122)),
+    ("lightseagreen", Color::rgb(32, 178, 170)),
+    ("lightskyblue", Color::rgb(135, 206, 250)),
+    ("lightslategray", Color::rgb(119, 136, 153)),
+    ("lightslategrey", Color::rgb(119, 136, 153)),
+    ("lightsteelblue", Color::rgb(176, 196, 222)),
+    ("lightyellow", Color::rgb(255, 255, 224)),
+    ("lime", Color::rgb(0, 255, 0)),
+    ("limegreen", Color::rgb(50, 205, 50)),
+    ("linen", Color::rgb(250, 240, 230)),
+    ("magenta", Color::rgb(255, 0, 255)),
+    ("maroon", Color::rgb(128, 0, 0)),
+    ("mediumaquamarine", Color::rgb(102, 205, 170)),
+    ("mediumblue", Color::rgb(0, 0, 205)),
+    ("mediumorchid", Color::rgb(186, 85, 211)),
+    ("mediumpurple", Color::rgb(147, 112, 219)),
+    ("mediumseagreen", Color::rgb(60, 179, 113)),
+    ("mediumslateblue", Color::rgb(123, 104, 238)),
+    ("mediumspringgreen", Color::rgb(0, 250, 154)),
+    ("mediumturquoise", Color::rgb(72, 209, 204)),
+    ("mediumvioletred", Color::rgb(199, 21, 133)),
+    ("midnightblue", Color::rgb(25, 25, 112)),
+    ("mintcream", Color::rgb(245, 255, 250)),
+    ("mistyrose", Color::rgb(255, 228, 225)),
+    ("moccasin", Color::rgb(255, 228, 181)),
+    ("navajowhite", Color::rgb(255, 222, 173)),
+    ("navy", Color::rgb(0, 0, 128)),
+    ("oldlace", Color::rgb(253, 245, 230)),
+    ("olive", Color::rgb(128, 128, 0)),
+    ("olivedrab", Color::rgb(107, 142, 35)),
+    ("orange", Color::rgb(255, 165, 0)),
+    ("orangered", Color::rgb(255, 69, 0)),
+    ("orchid", Color::rgb(218, 112, 214)),
+    ("palegoldenrod", Color::rgb(238, 232, 170)),
+    ("palegreen", Color::rgb(152, 251, 152)),
+    ("paleturquoise", Color::rgb(175, 238, 238)),
+    ("palevioletred", Color::rgb(219, 112, 147)),
+    ("papayawhip", Color::rgb(255, 239, 213)),
+    ("peachpuff", Color::rgb(255, 218, 185)),
+    ("peru", Color::rgb(205, 133, 63)),
+    ("pink", Color::rgb(255, 192, 203)),
+    ("plum", Color::rgb(221, 160, 221)),
+    ("powderblue", Color::rgb(176, 224, 230)),
+    ("purple", Color::rgb(128, 0, 128)),
+    ("rebeccapurple", Color::rgb(102, 51, 153)),
+    ("red", Color::rgb(255, 0, 0)),
+    ("rosybrown", Color::rgb(188, 143, 143)),
+    ("royalblue", Color::rgb(65, 105, 225)),
+    ("saddlebrown", Color::rgb(139, 69, 19)),
+    ("salmon", Color::rgb(250, 128, 114)),
+    ("sandybrown", Color::rgb(244, 164, 96)),
+    ("seagreen", Color::rgb(46, 139, 87)),
+    ("seashell", Color::rgb(255, 245, 238)),
+    ("sienna", Color::rgb(160, 82, 45)),
+    ("silver", Color::rgb(192, 192, 192)),
+    ("skyblue", Color::rgb(135, 206, 235)),
+    ("slateblue", Color::rgb(106, 90, 205)),
+    ("slategray", Color::rgb(112, 128, 144)),
+    ("slategrey", Color::rgb(112, 128, 144)),
+    ("snow", Color::rgb(255, 250, 250)),
+    ("springgreen", Color::rgb(0, 255, 127)),
+    ("steelblue", Color::rgb(70, 130, 180)),
+    ("tan", Color::rgb(210, 180, 140)),
+    ("teal", Color::rgb(0, 128, 128)),
+    ("thistle", Color::rgb(216, 191, 216)),
+    ("tomato", Color::rgb(255, 99, 71)),
+    ("turquoise", Color::rgb(64, 224, 208)),
+    ("violet", Color::rgb(238, 130, 238)),
+    ("wheat", Color::rgb(245, 222, 179)),
+    ("white", Color::rgb(255, 255, 255)),
+    ("whitesmoke", Color::rgb(245, 245, 245)),
+    ("yellow", Color::rgb(255, 255, 0)),
+    ("yellowgreen", Color::rgb(154, 205, 50)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(color: &str) -> (f64, f64, f64, f64) {
+        let (r, g, b, a) = parse_color(color).unwrap().rgba(1.0);
+        let round = |v: f64| (v * 255.0).round();
+        (round(r), round(g), round(b), (a * 100.0).round() / 100.0)
+    }
+
+    #[test]
+    fn test_parse_color() {
+        let (r, g, b, a) = parse_color("#FF0000").unwrap().rgba(1.0);
+        assert_eq!(r, 1.0);
+        assert_eq!(g, 0.0);
+        assert_eq!(b, 0.0);
+        assert_eq!(a, 1.0);
+    }
+
+    #[test]
+    fn test_parse_color_with_alpha() {
+        let (r, g, b, a) = parse_color("#00FF00").unwrap().rgba(0.5);
+        assert_eq!(r, 0.0);
+        assert_eq!(g, 1.0);
+        assert_eq!(b, 0.0);
+        assert_eq!(a, 0.5);
+    }
+
+    #[test]
+    fn test_css_syntax() {
+        assert_eq!(rgba("#f00"), (255.0, 0.0, 0.0, 1.0));
+        assert_eq!(rgba("#f008"), (255.0, 0.0, 0.0, 0.53));
+        assert_eq!(rgba("#81A1C180"), (129.0, 161.0, 193.0, 0.5));
+        assert_eq!(rgba("81A1C1"), (129.0, 161.0, 193.0, 1.0));
+        assert_eq!(rgba("Crimson"), (220.0, 20.0, 60.0, 1.0));
+        assert_eq!(rgba("transparent").3, 0.0);
+        assert_eq!(rgba("rgb(255, 128, 0)"), (255.0, 128.0, 0.0, 1.0));
+        assert_eq!(rgba("rgba(100%, 0%, 0%, 0.25)"), (255.0, 0.0, 0.0, 0.25));
+        assert_eq!(rgba("rgb(0 0 255 / 40%)"), (0.0, 0.0, 255.0, 0.4));
+        assert_eq!(rgba("hsl(120, 100%, 25%)"), (0.0, 128.0, 0.0, 1.0));
+        assert_eq!(
+            rgba("hsla(0.5turn 100% 50% / 0.5)"),
+            (0.0, 255.0, 255.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn test_hover_variant() {
+        let dark = parse_color("#2E3440").unwrap();
+        let light = parse_color("#FF8C00").unwrap();
+        assert!(dark.hover_variant().luminance() > dark.luminance());
+        assert!(light.hover_variant().luminance() < light.luminance());
+
+        let translucent = parse_color("rgba(46, 52, 64, 0.5)").unwrap();
+        assert_eq!(translucent.hover_variant().alpha, Some(0.5));
+    }
+
+    #[test]
+    fn test_foreground() {
+        let white = Color::rgb(255, 255, 255);
+        let black = Color::rgb(0, 0, 0);
+        assert_eq!((white.contrast(&black) * 10.0).round(), 210.0);
+
+        let dark = parse_color("#4169E1").unwrap();
+        assert_eq!(dark.foreground(MIN_CONTRAST), white);
+        let light = parse_color("#FF8C00").unwrap();
+        assert_eq!(light.foreground(MIN_CONTRAST), black);
+        assert!(light.contrast(&black) >= MIN_CONTRAST);
+    }
+
+    #[test]
+    fn test_invalid_colors() {
+        assert_eq!(
+            parse_color("#E0707").unwrap_err(),
+            "expected 3, 4, 6 or 8 hex digits"
+        );
+        assert_eq!(
+            parse_color("crimsn").unwrap_err(),
+            "not a hex color, rgb(), hsl() or color name"
+        );
+        assert!(parse_color("#gg0000").is_err());
+        assert!(parse_color("rgb(1, 2)").is_err());
+        assert!(parse_color("rgb(a, b, c)").is_err());
+        assert!(parse_color("hsl(120, 100, 25)").is_err());
+        assert!(parse_color("lab(50% 40 59)").is_err());
+    }
+}
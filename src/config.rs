@@ -1,93 +1,851 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use crate::color::parse_color;
+use crate::error::{ConfigError, Location};
+use crate::format::{join, LayoutFormat, Step};
+use crate::settings::{LayoutKind, Length, Settings, WrittenSettings};
+use crate::style::Style;
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
 /// Button configuration with optional recursive submenu support
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Button {
     pub label: String,
     /// Action to execute (mutually exclusive with children)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub action: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub text: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub keybind: Option<char>,
     /// Optional custom icon path (e.g., "/path/to/icon.png")
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub icon_path: Option<String>,
-    /// Optional custom icon character (Unicode/Nerd Font symbol)
-    #[serde(default)]
-    pub icon_char: Option<char>,
-    /// Optional base button color in hex format (e.g., "#81A1C1")
-    #[serde(default)]
+    /// Optional icon name from the freedesktop icon theme (e.g., "firefox")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_name: Option<String>,
+    /// Optional icon text (Unicode/Nerd Font symbols, emoji sequences);
+    /// `icon_char` is accepted as an older name
+    #[serde(default, alias = "icon_char", skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Optional base button color as a CSS color (e.g., "#81A1C1")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
-    /// Optional hover button color in hex format (e.g., "#5E81AC")
-    #[serde(default)]
+    /// Optional hover button color; derived from `color` if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hover_color: Option<String>,
+    /// Optional icon and label color; chosen for contrast with the wedge if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
     /// Whether to show the text label below the icon (default: false)
-    #[serde(default)]
-    pub show_label: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_label: Option<bool>,
+    /// Optional label font family (defaults to `settings.label_font`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// Optional icon font family (defaults to `settings.icon_font`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_font: Option<String>,
+    /// Optional icon glyph size (defaults to `settings.icon_size`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_size: Option<Length>,
+    /// Optional size of the wedge relative to its siblings (default: 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    /// Optional layout of this button's submenu (defaults to `settings.layout`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<LayoutKind>,
+    /// Style defaults for the buttons of this button's submenu
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<Style>,
     /// Optional submenu buttons (nested menu structure)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<Button>,
+    /// Optional reference to a named menu (e.g. "@power") used instead of `children`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submenu: Option<String>,
+    /// Named menu that `children` were expanded from, once resolved
+    #[serde(skip)]
+    pub expanded_from: Option<String>,
 }
 
 impl Button {
     /// Check if this button has a submenu
     pub fn has_submenu(&self) -> bool {
-        !self.children.is_empty()
+        !self.children.is_empty() || self.submenu.is_some()
+    }
+}
+
+/// A complete layout: the root menu plus its settings.
+///
+/// In the file this is either a bare array of buttons or an object of the
+/// form `{ "settings": {...}, "buttons": [...] }`.
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    /// Other layout files whose named menus are imported, relative to this file
+    pub include: Vec<String>,
+    pub settings: Settings,
+    /// Keys written in the file's `settings` block, which a merge takes
+    /// from this layout and which are written back out
+    pub settings_keys: BTreeSet<String>,
+    /// Style defaults for the buttons of the root menu
+    pub defaults: Style,
+    /// Named menus that buttons can reference with `"submenu": "@name"`
+    pub menus: BTreeMap<String, Vec<Button>>,
+    pub buttons: Vec<Button>,
+}
+
+/// Object form of a layout as it is written out
+#[derive(Serialize)]
+struct LayoutOutput<'a> {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    include: &'a Vec<String>,
+    #[serde(skip_serializing_if = "WrittenSettings::is_empty")]
+    settings: WrittenSettings<'a>,
+    #[serde(skip_serializing_if = "Style::is_empty")]
+    defaults: &'a Style,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    menus: &'a BTreeMap<String, Vec<Button>>,
+    buttons: &'a Vec<Button>,
+}
+
+impl Serialize for Layout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LayoutOutput {
+            include: &self.include,
+            settings: WrittenSettings {
+                settings: &self.settings,
+                keys: &self.settings_keys,
+            },
+            defaults: &self.defaults,
+            menus: &self.menus,
+            buttons: &self.buttons,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Object form of a layout; TOML requires a table at the top level
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayoutDocument {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    settings: Settings,
+    #[serde(default)]
+    defaults: Style,
+    #[serde(default)]
+    menus: BTreeMap<String, Vec<Button>>,
+    // Optional so that included files can hold only named menus
+    #[serde(default)]
+    buttons: Vec<Button>,
+}
+
+impl From<LayoutDocument> for Layout {
+    fn from(document: LayoutDocument) -> Self {
+        Layout {
+            include: document.include,
+            settings: document.settings,
+            settings_keys: BTreeSet::new(),
+            defaults: document.defaults,
+            menus: document.menus,
+            buttons: document.buttons,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Layout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LayoutVisitor;
+
+        impl<'de> Visitor<'de> for LayoutVisitor {
+            type Value = Layout;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an array of buttons or an object with \"buttons\"")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Layout, A::Error> {
+                let buttons = Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+                Ok(Layout {
+                    buttons,
+                    ..Layout::default()
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Layout, A::Error> {
+                let document =
+                    LayoutDocument::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(document.into())
+            }
+        }
+
+        deserializer.deserialize_any(LayoutVisitor)
+    }
+}
+
+impl Layout {
+    /// Whether this layout can be written as a bare button array
+    fn is_bare(&self) -> bool {
+        self.include.is_empty()
+            && self.settings_keys.is_empty()
+            && self.defaults.is_empty()
+            && self.menus.is_empty()
+    }
+
+    /// Layer `overlay` on top of this layout.
+    ///
+    /// Buttons are merged with [`merge_layouts`]; settings key by key, each
+    /// key the overlay sets replacing the base's. Both layouts should
+    /// already be resolved. Root style defaults are merged key by key too,
+    /// which only matters for `--check`, as styled layouts have none left.
+    pub fn merge(self, overlay: Layout) -> Layout {
+        let mut settings_keys = self.settings_keys;
+        settings_keys.extend(overlay.settings_keys.iter().cloned());
+        Layout {
+            settings: self
+                .settings
+                .overlay(&overlay.settings, &overlay.settings_keys),
+            settings_keys,
+            defaults: overlay.defaults.or(&self.defaults),
+            buttons: merge_layouts(self.buttons, overlay.buttons),
+            ..Layout::default()
+        }
+    }
+}
+
+/// Text of a layout or theme file, kept to locate errors in it
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// Source name for error messages
+    pub origin: String,
+    pub content: String,
+    pub format: LayoutFormat,
+    /// File the text was read from, if any
+    pub path: Option<PathBuf>,
+}
+
+impl Source {
+    /// Text that was not read from a file, such as stdin
+    pub fn new(content: &str, origin: &str, format: LayoutFormat) -> Self {
+        Self {
+            origin: origin.to_string(),
+            content: content.to_string(),
+            format,
+            path: None,
+        }
+    }
+
+    /// Read `path`, taking the format from its extension
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        Ok(Self {
+            origin: path.display().to_string(),
+            content: read_config(path)?,
+            format: LayoutFormat::from_path(path),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Where the value at `path` is written
+    pub fn locate(&self, path: &[Step]) -> Location {
+        self.format.locate(&self.content, &self.origin, path)
+    }
+}
+
+/// Read a configuration file, distinguishing a missing file from other I/O errors
+pub fn read_config(path: &Path) -> Result<String, ConfigError> {
+    let origin = path.display().to_string();
+    fs::read_to_string(path).map_err(|source| match source.kind() {
+        io::ErrorKind::NotFound => ConfigError::NotFound { origin },
+        _ => ConfigError::Io { origin, source },
+    })
+}
+
+/// Parse configuration in the given format.
+///
+/// `origin` names the source (a file path or e.g. `<stdin>`) in error messages.
+pub fn parse_config(
+    content: &str,
+    origin: &str,
+    format: LayoutFormat,
+) -> Result<Layout, ConfigError> {
+    let layout = parse_layout(content, origin, format)?;
+    let source = Source::new(content, origin, format);
+
+    if let Err((name, message)) = layout.settings.validate() {
+        return Err(ConfigError::Schema {
+            location: source.locate(&[Step::key("settings"), Step::key(name)]),
+            message: format!("settings.{} {}", name, message),
+        });
+    }
+    validate_style_colors(
+        &layout.defaults,
+        "defaults",
+        &source,
+        &[Step::key("defaults")],
+    )?;
+    validate_buttons(&layout.buttons, "", &source, &[Step::Buttons])?;
+    for (name, buttons) in &layout.menus {
+        let at = [Step::key("menus"), Step::key(name)];
+        validate_buttons(buttons, &format!("@{}", name), &source, &at)?;
+    }
+
+    Ok(layout)
+}
+
+/// Parse the layout without validating its values.
+///
+/// JSON layouts may also use comments, trailing commas and `\u{...}` escapes.
+pub fn parse_layout(
+    content: &str,
+    origin: &str,
+    format: LayoutFormat,
+) -> Result<Layout, ConfigError> {
+    let mut layout: Layout = format.deserialize(content, origin)?;
+    // A bare button array has no settings block
+    layout.settings_keys = format
+        .deserialize::<SettingsKeys>(content, origin)
+        .map(|keys| keys.settings.into_keys().collect())
+        .unwrap_or_default();
+    Ok(layout)
+}
+
+/// The keys of a layout's `settings` block, whatever their values.
+///
+/// Only a layout object has one; a bare button array is rejected rather than
+/// read as a struct from its first element.
+struct SettingsKeys {
+    settings: BTreeMap<String, de::IgnoredAny>,
+}
+
+impl<'de> Deserialize<'de> for SettingsKeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SettingsKeysVisitor;
+
+        impl<'de> Visitor<'de> for SettingsKeysVisitor {
+            type Value = SettingsKeys;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a layout object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SettingsKeys, A::Error> {
+                let mut settings = BTreeMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "settings" {
+                        settings = map.next_value()?;
+                    } else {
+                        map.next_value::<de::IgnoredAny>()?;
+                    }
+                }
+                Ok(SettingsKeys { settings })
+            }
+        }
+
+        deserializer.deserialize_map(SettingsKeysVisitor)
+    }
+}
+
+/// Serialize a layout in the given format.
+///
+/// Layouts with only buttons are written as a bare button array, except in
+/// TOML, which needs a table at the top level.
+pub fn serialize_config(layout: &Layout, format: LayoutFormat) -> Result<String, String> {
+    match format {
+        LayoutFormat::Json | LayoutFormat::Yaml if layout.is_bare() => {
+            format.serialize(&layout.buttons)
+        }
+        _ => format.serialize(layout),
     }
 }
 
-/// Load configuration from JSON file
-pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Vec<Button>> {
-    let content = fs::read_to_string(path)?;
-    let buttons: Vec<Button> = serde_json::from_str(&content)?;
-    Ok(buttons)
+/// Reject colors that `parse_color` cannot parse and weights that are not
+/// positive. `at` is where `buttons` are written in the source.
+fn validate_buttons(
+    buttons: &[Button],
+    parent_path: &str,
+    source: &Source,
+    at: &[Step],
+) -> Result<(), ConfigError> {
+    for (index, button) in buttons.iter().enumerate() {
+        let menu_path = menu_path(parent_path, &button.label);
+        let at = join(at, Step::Index(index));
+
+        validate_style_colors(&Style::of(button), &menu_path, source, &at)?;
+        if let Some(defaults) = &button.defaults {
+            let defaults_path = format!("{} defaults", menu_path);
+            let defaults_at = join(&at, Step::key("defaults"));
+            validate_style_colors(defaults, &defaults_path, source, &defaults_at)?;
+        }
+
+        if let Some(weight) = button.weight {
+            if !(weight > 0.0 && weight.is_finite()) {
+                return Err(ConfigError::Schema {
+                    location: source.locate(&join(&at, Step::key("weight"))),
+                    message: format!(
+                        "weight of {} must be greater than 0, got {}",
+                        menu_path, weight
+                    ),
+                });
+            }
+        }
+
+        let children_at = join(&at, Step::key("children"));
+        validate_buttons(&button.children, &menu_path, source, &children_at)?;
+    }
+    Ok(())
 }
 
-/// Parse hex color string to RGBA tuple
-pub fn parse_color(color_str: &str) -> (f64, f64, f64, f64) {
-    let color_str = color_str.trim_start_matches('#');
+fn validate_style_colors(
+    style: &Style,
+    menu_path: &str,
+    source: &Source,
+    at: &[Step],
+) -> Result<(), ConfigError> {
+    for (key, value) in style.colors() {
+        let Some(value) = value else {
+            continue;
+        };
+        // Theme references are checked when the theme is applied
+        if value.starts_with('@') {
+            continue;
+        }
+        if let Err(reason) = parse_color(value) {
+            return Err(ConfigError::InvalidColor {
+                location: source.locate(&join(at, Step::key(key))),
+                menu_path: menu_path.to_string(),
+                value: value.clone(),
+                reason,
+            });
+        }
+    }
+    Ok(())
+}
 
-    if let Ok(value) = u32::from_str_radix(color_str, 16) {
-        let r = ((value >> 16) & 0xFF) as f64 / 255.0;
-        let g = ((value >> 8) & 0xFF) as f64 / 255.0;
-        let b = (value & 0xFF) as f64 / 255.0;
-        (r, g, b, 1.0)
+/// Human-readable path to a button, e.g. "system > lock"
+pub fn menu_path(parent_path: &str, label: &str) -> String {
+    if parent_path.is_empty() {
+        label.to_string()
     } else {
-        // Fallback to gray
-        (0.5, 0.5, 0.5, 1.0)
+        format!("{} > {}", parent_path, label)
     }
 }
 
-/// Parse hex color with custom alpha
-pub fn parse_color_with_alpha(color_str: &str, alpha: f64) -> (f64, f64, f64, f64) {
-    let (r, g, b, _) = parse_color(color_str);
-    (r, g, b, alpha)
+/// Merge `overlay` on top of `base`, matching buttons by `label`.
+///
+/// An overlay button replaces the base button with the same label in place;
+/// if both have submenus, the children are merged the same way. Overlay
+/// buttons with new labels are appended after the base buttons.
+pub fn merge_layouts(base: Vec<Button>, overlay: Vec<Button>) -> Vec<Button> {
+    let mut merged = base;
+
+    for button in overlay {
+        match merged.iter_mut().find(|b| b.label == button.label) {
+            Some(existing) => {
+                let base_children = std::mem::take(&mut existing.children);
+                let mut button = button;
+                if button.has_submenu() && !base_children.is_empty() {
+                    button.children = merge_layouts(base_children, button.children);
+                }
+                *existing = button;
+            }
+            None => merged.push(button),
+        }
+    }
+
+    merged
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::settings::{Anchor, Direction, LayoutKind, Length};
+
+    #[test]
+    fn test_merge_settings() {
+        let parse = |content| parse_config(content, "<test>", LayoutFormat::Json).unwrap();
+        let base =
+            parse(r#"{"settings": {"radius_ratio": 0.25, "icon_size": 40, "wedge_gap": 2}}"#);
+        let overlay = parse(r#"{"settings": {"icon_size": "10mm", "radius": "40mm"}}"#);
+
+        let merged = base.clone().merge(overlay).settings;
+        assert_eq!(merged.radius_ratio, 0.25);
+        assert_eq!(merged.wedge_gap, 2.0);
+        assert_eq!(merged.icon_size, Length::Millimetres(10.0));
+        assert_eq!(merged.radius, Some(Length::Millimetres(40.0)));
+
+        // A key set back to its default still replaces the base's value
+        let overlay = parse(r#"{"settings": {"radius_ratio": 0.35}}"#);
+        let merged = base.merge(overlay).settings;
+        assert_eq!(merged.radius_ratio, 0.35);
+        assert_eq!(merged.icon_size, Length::Pixels(40.0));
+
+        // A bare array has no settings, even with a single button whose keys
+        // share names with settings
+        let base = parse(r#"{"settings": {"layout": "grid", "icon_size": 32}}"#);
+        let overlay = parse(r#"[{"label": "apps", "layout": "list", "icon_size": 20}]"#);
+        assert!(overlay.settings_keys.is_empty());
+        let merged = base.merge(overlay).settings;
+        assert_eq!(merged.layout, LayoutKind::Grid);
+        assert_eq!(merged.icon_size, Length::Pixels(32.0));
+    }
+
+    #[test]
+    fn test_merge_layouts() {
+        let base = parse_config(
+            r#"[
+                {"label": "lock", "action": "hyprlock", "text": "Lock"},
+                {"label": "power", "text": "Power", "children": [
+                    {"label": "reboot", "action": "systemctl reboot"},
+                    {"label": "shutdown", "action": "systemctl poweroff"}
+                ]}
+            ]"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap()
+        .buttons;
+        let overlay = parse_config(
+            r#"[
+                {"label": "lock", "action": "swaylock", "text": "Lock"},
+                {"label": "power", "text": "Power", "children": [
+                    {"label": "shutdown", "action": "poweroff"},
+                    {"label": "hibernate", "action": "systemctl hibernate"}
+                ]},
+                {"label": "terminal", "action": "kitty"}
+            ]"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap()
+        .buttons;
+
+        let merged = merge_layouts(base, overlay);
+        let labels: Vec<&str> = merged.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["lock", "power", "terminal"]);
+        assert_eq!(merged[0].action, "swaylock");
+
+        let children: Vec<(&str, &str)> = merged[1]
+            .children
+            .iter()
+            .map(|b| (b.label.as_str(), b.action.as_str()))
+            .collect();
+        assert_eq!(
+            children,
+            [
+                ("reboot", "systemctl reboot"),
+                ("shutdown", "poweroff"),
+                ("hibernate", "systemctl hibernate")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        let err = parse_config(
+            "[\n  {\"label\": \"lock\" \"text\": \"Lock\"}\n]",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::Syntax { .. }));
+        assert_eq!(
+            err.to_string(),
+            "layout:2:20: syntax error: expected `,` or `}`"
+        );
+
+        let err = parse_config("[{\"text\": \"Lock\"}]", "layout", LayoutFormat::Json).unwrap_err();
+        assert!(matches!(err, ConfigError::Schema { .. }));
+
+        let err = parse_config(
+            "[{\"label\": \"power\", \"children\": [\n  {\"label\": \"off\", \"color\": \"crimsn\"}\n]}]",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:36: invalid color 'crimsn' in power > off: not a hex color, rgb(), hsl() or color name"
+        );
+
+        // Columns count characters, not bytes, after a glyph
+        let err = parse_config(
+            "[{\"label\": \"lock\", \"icon\": \"\u{f023}\", \"color\": \"blah\"}]",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:47: invalid color 'blah' in lock: not a hex color, rgb(), hsl() or color name"
+        );
+        let err = parse_config(
+            r#"[{"label": "lock", "icon": "\u{f023}", "color": "blah"}]"#,
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("layout:1:54: "));
+        let err = parse_config(
+            "[{\"label\": \"lock\", \"icon\": \"\u{f023}\", \"weight\": \"x\"}]",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:45: invalid type: string \"x\", expected f64"
+        );
+    }
 
     #[test]
-    fn test_parse_color() {
-        let (r, g, b, a) = parse_color("#FF0000");
-        assert_eq!(r, 1.0);
-        assert_eq!(g, 0.0);
-        assert_eq!(b, 0.0);
-        assert_eq!(a, 1.0);
+    fn test_parse_config_relaxed() {
+        let buttons = parse_config(
+            r#"[
+                // Trailing commas and Rust-style escapes, as in the README
+                {"label": "lock", "action": "hyprlock", "icon_char": "\u{f023}",},
+            ]"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap()
+        .buttons;
+        assert_eq!(buttons[0].icon.as_deref(), Some("\u{f023}"));
     }
 
     #[test]
-    fn test_parse_color_with_alpha() {
-        let (r, g, b, a) = parse_color_with_alpha("#00FF00", 0.5);
-        assert_eq!(r, 0.0);
-        assert_eq!(g, 1.0);
-        assert_eq!(b, 0.0);
-        assert_eq!(a, 0.5);
+    fn test_parse_config_icon() {
+        let buttons = parse_config(
+            r#"[
+                {"label": "de", "action": "true", "icon": "🇩🇪"},
+                {"label": "family", "action": "true", "icon": "👩‍👩‍👧"},
+                {"label": "wave", "action": "true", "icon_char": "👋🏽"}
+            ]"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap()
+        .buttons;
+        assert_eq!(buttons[0].icon.as_deref(), Some("🇩🇪"));
+        assert_eq!(buttons[1].icon.as_deref(), Some("👩‍👩‍👧"));
+        assert_eq!(buttons[2].icon.as_deref(), Some("👋🏽"));
+
+        let json = serialize_config(
+            &Layout {
+                buttons,
+                ..Layout::default()
+            },
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        assert!(json.contains("\"icon\": \"👋🏽\""));
+        assert!(!json.contains("icon_char"));
+    }
+
+    #[test]
+    fn test_parse_config_formats() {
+        let toml = r##"
+[[buttons]]
+label = "power"
+text = "Power"
+color = "#B22222"
+
+[[buttons.children]]
+label = "shutdown"
+action = "systemctl poweroff"
+keybind = "s"
+"##;
+        let yaml = r##"
+- label: power
+  text: Power
+  color: "#B22222"
+  children:
+    - label: shutdown
+      action: systemctl poweroff
+      keybind: s
+"##;
+        for (content, format) in [(toml, LayoutFormat::Toml), (yaml, LayoutFormat::Yaml)] {
+            let buttons = parse_config(content, "<test>", format).unwrap().buttons;
+            assert_eq!(buttons[0].color.as_deref(), Some("#B22222"));
+            assert_eq!(buttons[0].children[0].keybind, Some('s'));
+        }
+
+        let err = parse_config(
+            "- label: off\n  color: crimsn\n",
+            "layout.yaml",
+            LayoutFormat::Yaml,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout.yaml:2:10: invalid color 'crimsn' in off: not a hex color, rgb(), hsl() or color name"
+        );
+
+        let err =
+            parse_config("[[buttons]]\ntext = 1\n", "layout.toml", LayoutFormat::Toml).unwrap_err();
+        assert!(matches!(err, ConfigError::Schema { .. }));
+        assert!(err.to_string().starts_with("layout.toml:"));
+    }
+
+    #[test]
+    fn test_serialize_config_round_trip() {
+        let bare = r#"[{"label": "power", "text": "Power", "icon_char": "\u{f011}", "show_label": true,
+                 "children": [{"label": "off", "action": "poweroff", "keybind": "o"}]}]"#;
+        let with_settings = r#"{"settings": {"icon_size": 32, "label_font": "Inter",
+                                 "radius": "40mm", "max_radius": 400},
+                 "buttons": [{"label": "lock", "action": "hyprlock"}]}"#;
+
+        for content in [bare, with_settings] {
+            let layout = parse_config(content, "<test>", LayoutFormat::Json).unwrap();
+            let expected = serde_json::to_value(&layout).unwrap();
+
+            for format in [LayoutFormat::Json, LayoutFormat::Toml, LayoutFormat::Yaml] {
+                let text = serialize_config(&layout, format).unwrap();
+                let parsed = parse_config(&text, "<test>", format).unwrap();
+                assert_eq!(serde_json::to_value(&parsed).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialize_config_settings_keys() {
+        let parse = |content: &str, format| parse_config(content, "<test>", format).unwrap();
+        let system = parse(
+            r#"{"settings": {"radius_ratio": 0.2, "wedge_gap": 3}}"#,
+            LayoutFormat::Json,
+        );
+        // The second sets only a default value, which still overrides the
+        // system layout's
+        let icon_size = r#"{"settings": {"icon_size": 32},
+                 "buttons": [{"label": "lock", "action": "hyprlock"}]}"#;
+        let default_ratio = r#"{"settings": {"radius_ratio": 0.35},
+                 "buttons": [{"label": "lock", "action": "hyprlock"}]}"#;
+
+        for content in [icon_size, default_ratio] {
+            let layout = parse(content, LayoutFormat::Json);
+            let expected = system.clone().merge(layout.clone()).settings;
+
+            // Only the keys the file sets are written, so the converted file
+            // merges the same way
+            for format in [LayoutFormat::Json, LayoutFormat::Toml, LayoutFormat::Yaml] {
+                let text = serialize_config(&layout, format).unwrap();
+                let converted = parse(&text, format);
+                assert_eq!(converted.settings_keys, layout.settings_keys);
+                assert_eq!(system.clone().merge(converted).settings, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_config_weight() {
+        let layout = parse_config(
+            r#"[{"label": "lock", "action": "hyprlock", "weight": 2}, {"label": "logout"}]"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(layout.buttons[0].weight, Some(2.0));
+        assert_eq!(layout.buttons[1].weight, None);
+
+        let err = parse_config(
+            r#"[{"label": "system", "children": [{"label": "lock", "weight": 0}]}]"#,
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:63: weight of system > lock must be greater than 0, got 0"
+        );
+
+        let err = parse_config(
+            "[{\"label\": \"lock\", \"weight\": 2},\n {\"label\": \"logout\", \"weight\": 0}]",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:32: weight of logout must be greater than 0, got 0"
+        );
+    }
+
+    #[test]
+    fn test_parse_config_layout() {
+        let layout = parse_config(
+            r#"{
+                "settings": {"layout": "list"},
+                "buttons": [{"label": "apps", "layout": "grid", "children": [{"label": "term"}]}]
+            }"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(layout.settings.layout, LayoutKind::List);
+        assert_eq!(layout.buttons[0].layout, Some(LayoutKind::Grid));
+        assert_eq!(layout.buttons[0].children[0].layout, None);
+    }
+
+    #[test]
+    fn test_parse_config_settings() {
+        let layout = parse_config(
+            r#"{
+                "settings": {"radius_ratio": 0.25, "hover_scale": 1.2},
+                "buttons": [{"label": "lock", "action": "hyprlock"}]
+            }"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(layout.settings.radius_ratio, 0.25);
+        assert_eq!(layout.settings.hover_scale, 1.2);
+        assert_eq!(layout.settings.icon_size, Settings::default().icon_size);
+        assert_eq!(layout.buttons[0].label, "lock");
+
+        let layout = parse_config(
+            "settings:\n  sweep_angle: 180\n  direction: counter-clockwise\n  anchor: bottom-left\nbuttons: []\n",
+            "<test>",
+            LayoutFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(layout.settings.sweep_angle, 180.0);
+        assert_eq!(layout.settings.direction, Direction::CounterClockwise);
+        assert_eq!(layout.settings.anchor, Anchor::BottomLeft);
+
+        let err = parse_config(
+            "{\"settings\": {\"ring_size\": 1}, \"buttons\": []}",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::Schema { .. }));
+
+        let err = parse_config(
+            "{\n  \"settings\": {\"wedge_alpha\": 2},\n  \"buttons\": []\n}",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:31: settings.wedge_alpha must be between 0 and 1, got 2"
+        );
+
+        let err = parse_config(
+            "{\"settings\": {\"border_color\": \"blak\"}, \"buttons\": []}",
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:36: settings.border_color 'blak' is not a valid color: \
+             not a hex color, rgb(), hsl() or color name"
+        );
     }
 }
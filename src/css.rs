@@ -0,0 +1,159 @@
+//! Ring styling from the GTK stylesheet.
+//!
+//! The ring is drawn with cairo rather than built from widgets, so GTK never
+//! applies the stylesheet's `button` rules to it. Instead each wedge gets a
+//! style context for a `button` node below the drawing area, with a class
+//! named after the button's label (`button.lock`) and the prelight state for
+//! `:hover`, and the drawing code reads its colors, border and font from
+//! there. The overlay behind the ring takes the drawing area's own
+//! `background-color`, which can be set through its `overlay` class.
+
+use crate::color::Color;
+use gtk::gdk;
+use gtk::glib::prelude::StaticType;
+use gtk::prelude::*;
+use gtk::{StateFlags, StyleContext};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Style class on the drawing area, for the overlay color
+pub const OVERLAY_CLASS: &str = "overlay";
+
+/// Colors for one wedge state; `None` where the stylesheet sets nothing
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StateStyle {
+    pub background: Option<Color>,
+    pub foreground: Option<Color>,
+    pub border: Option<Color>,
+}
+
+/// Everything the stylesheet says about one wedge
+#[derive(Clone, Debug, Default)]
+pub struct WedgeStyle {
+    pub normal: StateStyle,
+    /// The `:hover` state, only where it differs from the normal state
+    pub hover: StateStyle,
+    pub font: Option<String>,
+    pub border_width: f64,
+}
+
+thread_local! {
+    // The stylesheet is loaded once at startup, so each label's style is
+    // looked up once rather than on every frame.
+    static STYLE_CACHE: RefCell<HashMap<String, WedgeStyle>> = RefCell::new(HashMap::new());
+}
+
+/// Style of the wedge for the button labelled `label`
+pub fn wedge_style(widget: &impl IsA<gtk::Widget>, label: &str) -> WedgeStyle {
+    STYLE_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry(label.to_string())
+            .or_insert_with(|| lookup(widget.as_ref(), label))
+            .clone()
+    })
+}
+
+/// Overlay color from the drawing area's `background-color`, if it sets one
+pub fn overlay_color(widget: &impl IsA<gtk::Widget>) -> Option<Color> {
+    let context = widget.style_context();
+    background(&context, context.state())
+}
+
+fn lookup(widget: &gtk::Widget, label: &str) -> WedgeStyle {
+    let context = wedge_context(widget, label);
+
+    // Color and font are inherited, so only count them when the button rules
+    // change them from what the drawing area already has
+    let parent = widget.style_context();
+    let inherited = parent.color(parent.state());
+    let inherited_font = font_family(&parent, parent.state());
+
+    let state = |flags: StateFlags| {
+        context.set_state(flags);
+        let foreground = context.color(flags);
+        let border = context
+            .style_property_for_state("border-top-color", flags)
+            .get::<gdk::RGBA>()
+            .ok();
+        StateStyle {
+            background: background(&context, flags),
+            foreground: (foreground != inherited).then(|| to_color(&foreground)),
+            border: border.map(|border| to_color(&border)),
+        }
+    };
+    let hover = state(StateFlags::PRELIGHT);
+    let normal = state(StateFlags::NORMAL);
+    let changed = |a: Option<Color>, b: Option<Color>| if a != b { a } else { None };
+
+    let font = font_family(&context, StateFlags::NORMAL)
+        .filter(|font| Some(font) != inherited_font.as_ref());
+    // GTK computes the width as zero when border-style is none
+    let border_width = context.border(StateFlags::NORMAL).top as f64;
+
+    WedgeStyle {
+        normal,
+        hover: StateStyle {
+            background: changed(hover.background, normal.background),
+            foreground: changed(hover.foreground, normal.foreground),
+            border: changed(hover.border, normal.border),
+        },
+        font,
+        border_width,
+    }
+}
+
+/// A style context for a `button` node with the label's class, below `widget`
+fn wedge_context(widget: &gtk::Widget, label: &str) -> StyleContext {
+    let path = widget.path();
+    let node = path.append_type(gtk::Button::static_type());
+    path.iter_set_object_name(node, Some("button"));
+    path.iter_add_class(node, &class_name(label));
+
+    let context = StyleContext::new();
+    context.set_path(&path);
+    context.set_parent(Some(&widget.style_context()));
+    context
+}
+
+/// A CSS class for `label`: lowercase, with anything else replaced by `-`
+fn class_name(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// The `background-color` in `state`, unless it is fully transparent
+fn background(context: &StyleContext, state: StateFlags) -> Option<Color> {
+    context
+        .style_property_for_state("background-color", state)
+        .get::<gdk::RGBA>()
+        .ok()
+        .filter(|color| color.alpha() > 0.0)
+        .map(|color| to_color(&color))
+}
+
+fn font_family(context: &StyleContext, state: StateFlags) -> Option<String> {
+    context
+        .style_property_for_state("font", state)
+        .get::<gtk::pango::FontDescription>()
+        .ok()
+        .and_then(|font| font.family())
+        .map(|family| family.to_string())
+}
+
+fn to_color(color: &gdk::RGBA) -> Color {
+    Color {
+        r: color.red(),
+        g: color.green(),
+        b: color.blue(),
+        alpha: Some(color.alpha()),
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use thiserror::Error;
+
+/// Where in the configuration a problem was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// File path, or a placeholder such as `<stdin>` for non-file sources
+    pub origin: String,
+    /// 1-based line and column, when known
+    pub position: Option<(usize, usize)>,
+}
+
+impl Location {
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.to_string(),
+            position: None,
+        }
+    }
+
+    pub fn at(origin: &str, line: usize, column: usize) -> Self {
+        Self {
+            origin: origin.to_string(),
+            position: Some((line, column)),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}:{}", self.origin, line, column),
+            None => write!(f, "{}", self.origin),
+        }
+    }
+}
+
+/// Convert a byte offset into a 1-based line and column
+pub fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Drop the " at line X column Y" that serde_json and serde_yaml append to
+/// their messages, which the location already covers
+pub fn strip_position(message: String) -> String {
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
+/// Convert serde_json's line and byte column into a character column in
+/// `content`, counted as [`line_column`] counts it
+fn json_column(content: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let mut end = (line_start + column).min(content.len());
+    while !content.is_char_boundary(end) {
+        end += 1;
+    }
+    content[line_start..end].chars().count()
+}
+
+/// Errors raised while locating, reading or validating a layout
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{origin}: file not found")]
+    NotFound { origin: String },
+
+    #[error("no layout file found (searched {searched})")]
+    NoLayout { searched: String },
+
+    #[error("{origin}: {source}")]
+    Io {
+        origin: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("{location}: syntax error: {message}")]
+    Syntax { location: Location, message: String },
+
+    #[error("{location}: {message}")]
+    Schema { location: Location, message: String },
+
+    #[error("{location}: invalid color '{value}' in {menu_path}: {reason}")]
+    InvalidColor {
+        location: Location,
+        menu_path: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("{location}: unknown menu '@{name}' in {menu_path}")]
+    UnknownMenu {
+        location: Location,
+        menu_path: String,
+        name: String,
+    },
+
+    #[error("{location}: unknown color '@{name}' in {menu_path} ({theme})")]
+    UnknownColor {
+        location: Location,
+        menu_path: String,
+        name: String,
+        /// The theme that was searched, or a note that none is selected
+        theme: String,
+    },
+
+    #[error("{location}: unknown Nerd Font icon '{name}' in {menu_path}")]
+    UnknownIcon {
+        location: Location,
+        menu_path: String,
+        name: String,
+    },
+
+    #[error("theme '{name}' not found (looked for themes/{name}.json, .toml, .yaml and .yml in the config directories)")]
+    UnknownTheme { name: String },
+
+    #[error("{location}: menu reference cycle: {chain}")]
+    MenuCycle { location: Location, chain: String },
+
+    #[error("{location}: include cycle: {chain}")]
+    IncludeCycle { location: Location, chain: String },
+}
+
+impl ConfigError {
+    /// Classify a serde_json error in `content`, keeping its line and column
+    pub fn from_json(origin: &str, content: &str, error: serde_json::Error) -> Self {
+        use serde_json::error::Category;
+
+        let column = json_column(content, error.line(), error.column());
+        let location = Location::at(origin, error.line(), column);
+        let message = strip_position(error.to_string());
+
+        match error.classify() {
+            Category::Data => ConfigError::Schema { location, message },
+            Category::Syntax | Category::Eof | Category::Io => {
+                ConfigError::Syntax { location, message }
+            }
+        }
+    }
+}
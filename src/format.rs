@@ -0,0 +1,246 @@
+use crate::error::{line_column, strip_position, ConfigError, Location};
+use crate::lenient;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::Serialize;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// One step on the way from the top of a layout file to a value in it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// The root menu: the `buttons` of a layout object, or a bare array
+    Buttons,
+    Key(String),
+    Index(usize),
+}
+
+impl Step {
+    pub fn key(key: &str) -> Self {
+        Step::Key(key.to_string())
+    }
+}
+
+/// `path` followed by one more step
+pub fn join(path: &[Step], step: Step) -> Vec<Step> {
+    let mut path = path.to_vec();
+    path.push(step);
+    path
+}
+
+/// Error raised on reaching the value being located, so that the parser
+/// reports where it is
+const FOUND: &str = "located";
+
+/// On-disk syntax of a layout file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl LayoutFormat {
+    /// Detect the format from the file extension; anything unknown is JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("toml") => LayoutFormat::Toml,
+            Some("yaml" | "yml") => LayoutFormat::Yaml,
+            _ => LayoutFormat::Json,
+        }
+    }
+
+    /// Deserialize `content`, mapping errors to their line and column in `origin`
+    pub fn deserialize<T: DeserializeOwned>(
+        self,
+        content: &str,
+        origin: &str,
+    ) -> Result<T, ConfigError> {
+        self.deserialize_seed(content, origin, PhantomData::<T>)
+    }
+
+    /// Where the parser finds the value at `path` in `content`. The location
+    /// has no position when the value is not there.
+    pub fn locate(self, content: &str, origin: &str, path: &[Step]) -> Location {
+        match self.deserialize_seed(content, origin, Locate(path)) {
+            Err(ConfigError::Schema { location, message }) if message.ends_with(FOUND) => location,
+            _ => Location::new(origin),
+        }
+    }
+
+    fn deserialize_seed<T>(
+        self,
+        content: &str,
+        origin: &str,
+        seed: impl for<'de> DeserializeSeed<'de, Value = T>,
+    ) -> Result<T, ConfigError> {
+        match self {
+            LayoutFormat::Json => {
+                let json = lenient::normalize(content, origin)?;
+                let mut deserializer = serde_json::Deserializer::from_str(&json);
+                seed.deserialize(&mut deserializer)
+                    .and_then(|value| deserializer.end().map(|()| value))
+                    .map_err(|e| ConfigError::from_json(origin, content, e))
+            }
+            LayoutFormat::Toml => {
+                // Parse once untyped so syntax errors are told apart from schema errors
+                let syntax = content.parse::<toml::Table>().err();
+                seed.deserialize(toml::Deserializer::new(content))
+                    .map_err(|e| {
+                        let location = match e.span() {
+                            Some(span) => {
+                                let (line, column) = line_column(content, span.start);
+                                Location::at(origin, line, column)
+                            }
+                            None => Location::new(origin),
+                        };
+                        let message = e.message().to_string();
+                        if syntax.is_some() {
+                            ConfigError::Syntax { location, message }
+                        } else {
+                            ConfigError::Schema { location, message }
+                        }
+                    })
+            }
+            LayoutFormat::Yaml => {
+                let syntax = serde_yaml::from_str::<serde_yaml::Value>(content).err();
+                seed.deserialize(serde_yaml::Deserializer::from_str(content))
+                    .map_err(|e| {
+                        let location = match e.location() {
+                            Some(mark) => {
+                                let (line, column) = line_column(content, mark.index());
+                                Location::at(origin, line, column)
+                            }
+                            None => Location::new(origin),
+                        };
+                        let message = strip_position(e.to_string());
+                        if syntax.is_some() {
+                            ConfigError::Syntax { location, message }
+                        } else {
+                            ConfigError::Schema { location, message }
+                        }
+                    })
+            }
+        }
+    }
+
+    /// Serialize `value` in this format
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, String> {
+        match self {
+            LayoutFormat::Json => {
+                // Match the four-space indentation of the shipped layouts
+                let mut output = Vec::new();
+                let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+                let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+                value
+                    .serialize(&mut serializer)
+                    .map_err(|e| e.to_string())?;
+                output.push(b'\n');
+                String::from_utf8(output).map_err(|e| e.to_string())
+            }
+            LayoutFormat::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+            LayoutFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Follows a path through the document, skipping everything off it, and
+/// fails with `FOUND` at its end
+struct Locate<'a>(&'a [Step]);
+
+impl<'de> DeserializeSeed<'de> for Locate<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Locate<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<(), E> {
+        self.leaf()
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let rest = match self.0 {
+            [] => return Err(de::Error::custom(FOUND)),
+            // A bare button array is the root menu itself
+            [Step::Buttons, rest @ ..] => rest,
+            path => path,
+        };
+        if rest.is_empty() {
+            return Err(de::Error::custom(FOUND));
+        }
+        let mut index = 0;
+        loop {
+            let found = match &rest[0] {
+                Step::Index(i) if *i == index => seq.next_element_seed(Locate(&rest[1..]))?,
+                _ => seq.next_element::<IgnoredAny>()?.map(drop),
+            };
+            if found.is_none() {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Some((step, rest)) = self.0.split_first() else {
+            return Err(de::Error::custom(FOUND));
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            match step {
+                Step::Key(name) if *name == key => map.next_value_seed(Locate(rest))?,
+                Step::Buttons if key == "buttons" => map.next_value_seed(Locate(rest))?,
+                _ => drop(map.next_value::<IgnoredAny>()?),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Locate<'_> {
+    /// A scalar: the end of the path, or a value the path cannot go through
+    fn leaf<E: de::Error>(self) -> Result<(), E> {
+        match self.0 {
+            [] => Err(E::custom(FOUND)),
+            _ => Ok(()),
+        }
+    }
+}
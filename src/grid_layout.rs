@@ -0,0 +1,183 @@
+//! Grid layout: rows of square cells, in the style of wlogout.
+
+use crate::menu_layout::{
+    cycle, draw_icon, draw_label, fill_button, paint_order, rounded_rect_path, MenuButton,
+    MenuLayout, Nav, LABEL_PADDING,
+};
+use crate::settings::Settings;
+use gtk::gdk::cairo::Context;
+use gtk::pango;
+
+/// Cells of `cell` pixels in rows of `columns`, the first at (x, y)
+pub struct GridLayout {
+    pub x: f64,
+    pub y: f64,
+    pub columns: usize,
+    pub cell: f64,
+    pub spacing: f64,
+}
+
+impl GridLayout {
+    /// A grid for `count` buttons in a `width`x`height` window on a monitor
+    /// with `pixels_per_mm`, with the point of the grid matching the anchor
+    /// placed on the anchor
+    pub fn new(
+        count: usize,
+        width: f64,
+        height: f64,
+        pixels_per_mm: f64,
+        settings: &Settings,
+    ) -> Self {
+        let columns = match settings.grid_columns {
+            0 => (count as f64).sqrt().ceil() as usize,
+            columns => columns,
+        }
+        .clamp(1, count.max(1));
+        let rows = count.div_ceil(columns).max(1);
+
+        let cell = settings.cell_size.pixels(pixels_per_mm);
+        let spacing = settings.cell_spacing;
+        let extent = |cells: usize| cells as f64 * cell + (cells - 1) as f64 * spacing;
+        let (anchor_x, anchor_y) = settings.anchor.position(width, height);
+        let (fraction_x, fraction_y) = settings.anchor.fractions();
+
+        Self {
+            x: anchor_x - extent(columns) * fraction_x,
+            y: anchor_y - extent(rows) * fraction_y,
+            columns,
+            cell,
+            spacing,
+        }
+    }
+
+    /// Center of the cell of button `index`
+    fn cell_center(&self, index: usize) -> (f64, f64) {
+        let (row, column) = (index / self.columns, index % self.columns);
+        let step = self.cell + self.spacing;
+        (
+            self.x + column as f64 * step + self.cell / 2.0,
+            self.y + row as f64 * step + self.cell / 2.0,
+        )
+    }
+}
+
+impl MenuLayout for GridLayout {
+    /// A square cell, grown around its center
+    fn button_path(&self, cr: &Context, index: usize, scale: f64, settings: &Settings) {
+        let (center_x, center_y) = self.cell_center(index);
+        let size = self.cell * scale;
+        rounded_rect_path(
+            cr,
+            center_x - size / 2.0,
+            center_y - size / 2.0,
+            size,
+            size,
+            settings.corner_radius,
+        );
+    }
+
+    fn draw(
+        &self,
+        cr: &Context,
+        buttons: &[MenuButton],
+        hover_button: i32,
+        scales: &[f64],
+        settings: &Settings,
+    ) {
+        for i in paint_order(buttons.len(), hover_button) {
+            let button = &buttons[i];
+            let (center_x, center_y) = self.cell_center(i);
+            let scale = scales.get(i).copied().unwrap_or(1.0);
+            let size = self.cell * scale;
+
+            self.button_path(cr, i, scale, settings);
+            let foreground = fill_button(cr, button, i as i32 == hover_button);
+            draw_icon(
+                cr,
+                button,
+                center_x,
+                center_y,
+                button.icon_size * scale,
+                foreground,
+            );
+
+            if button.show_label {
+                let width = (size - 2.0 * LABEL_PADDING).max(0.0);
+                draw_label(
+                    cr,
+                    button,
+                    center_x - width / 2.0,
+                    center_y + settings.label_offset,
+                    width,
+                    pango::Alignment::Center,
+                    foreground,
+                    settings,
+                );
+            }
+        }
+    }
+
+    /// Arrow keys move between neighbouring cells, stopping at the edges
+    fn neighbor(&self, from: Option<usize>, nav: Nav, count: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let Some(from) = from else {
+            return Some(0);
+        };
+
+        let (row, column) = (from / self.columns, from % self.columns);
+        let rows = count.div_ceil(self.columns);
+        Some(match nav {
+            Nav::Next | Nav::Previous => cycle(from, nav, count),
+            Nav::Left if column > 0 => from - 1,
+            Nav::Right if column + 1 < self.columns && from + 1 < count => from + 1,
+            Nav::Up if row > 0 => from - self.columns,
+            Nav::Down if from + self.columns < count => from + self.columns,
+            // The last row may be short
+            Nav::Down if row + 1 < rows => count - 1,
+            _ => from,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbor() {
+        // Seven buttons in rows of three; the last row holds only button 6
+        let grid = GridLayout {
+            x: 0.0,
+            y: 0.0,
+            columns: 3,
+            cell: 10.0,
+            spacing: 0.0,
+        };
+        let neighbor = |from, nav| grid.neighbor(Some(from), nav, 7);
+
+        assert_eq!(grid.neighbor(None, Nav::Down, 7), Some(0));
+        assert_eq!(grid.neighbor(None, Nav::Down, 0), None);
+
+        assert_eq!(neighbor(0, Nav::Right), Some(1));
+        assert_eq!(neighbor(4, Nav::Left), Some(3));
+        assert_eq!(neighbor(4, Nav::Up), Some(1));
+        assert_eq!(neighbor(1, Nav::Down), Some(4));
+
+        // Edges stop the move
+        assert_eq!(neighbor(2, Nav::Right), Some(2));
+        assert_eq!(neighbor(3, Nav::Left), Some(3));
+        assert_eq!(neighbor(1, Nav::Up), Some(1));
+        assert_eq!(neighbor(6, Nav::Down), Some(6));
+        assert_eq!(neighbor(6, Nav::Right), Some(6));
+
+        // Moving down onto the short last row lands on its last button
+        assert_eq!(neighbor(3, Nav::Down), Some(6));
+        assert_eq!(neighbor(5, Nav::Down), Some(6));
+        assert_eq!(neighbor(6, Nav::Up), Some(3));
+
+        assert_eq!(neighbor(6, Nav::Next), Some(0));
+        assert_eq!(neighbor(0, Nav::Previous), Some(6));
+    }
+}
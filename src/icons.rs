@@ -0,0 +1,100 @@
+//! Image icons for `icon_path` and `icon_name`.
+//!
+//! Files are decoded with gdk-pixbuf, which handles PNG and other raster
+//! formats as well as SVG (through librsvg's pixbuf loader); names are
+//! looked up in the freedesktop icon theme. Either way the result is
+//! converted once into a cairo surface and cached per source, size and
+//! scale factor rather than decoded again on every frame.
+
+use gtk::gdk::cairo::Surface;
+use gtk::gdk::prelude::GdkPixbufExt;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::prelude::IconThemeExt;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A decoded icon and its size in logical pixels
+#[derive(Clone, Debug)]
+pub struct Icon {
+    pub surface: Surface,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Where an icon comes from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Source {
+    File(String),
+    Themed(String),
+}
+
+thread_local! {
+    // Failed loads are cached too, so a broken icon is reported only once.
+    static ICON_CACHE: RefCell<HashMap<(Source, u32, i32), Option<Icon>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Load the image at `path`, fitted into a `size`x`size` square
+pub fn load_icon_file(path: &str, size: u32, scale: i32) -> Option<Icon> {
+    cached(Source::File(path.to_string()), size, scale)
+}
+
+/// Look up `name` in the icon theme at `size` logical pixels
+pub fn load_theme_icon(name: &str, size: u32, scale: i32) -> Option<Icon> {
+    cached(Source::Themed(name.to_string()), size, scale)
+}
+
+fn cached(source: Source, size: u32, scale: i32) -> Option<Icon> {
+    ICON_CACHE.with(|cache| {
+        if let Some(icon) = cache.borrow().get(&(source.clone(), size, scale)) {
+            return icon.clone();
+        }
+        let icon = decode(&source, size, scale);
+        cache
+            .borrow_mut()
+            .insert((source, size, scale), icon.clone());
+        icon
+    })
+}
+
+fn decode(source: &Source, size: u32, scale: i32) -> Option<Icon> {
+    // Decode at device resolution; the surface's device scale maps it back
+    let pixels = size as i32 * scale;
+    let pixbuf = match source {
+        Source::File(path) => Pixbuf::from_file_at_scale(path, pixels, pixels, true)
+            .map_err(|e| log::warn!("Failed to load icon {}: {}", path, e))
+            .ok()?,
+        Source::Themed(name) => {
+            let theme = gtk::IconTheme::default()?;
+            match theme.load_icon_for_scale(
+                name,
+                size as i32,
+                scale,
+                gtk::IconLookupFlags::FORCE_SIZE,
+            ) {
+                Ok(Some(pixbuf)) => pixbuf,
+                Ok(None) => {
+                    log::warn!("Icon '{}' not found in the icon theme", name);
+                    return None;
+                }
+                Err(e) => {
+                    log::warn!("Failed to load icon '{}': {}", name, e);
+                    return None;
+                }
+            }
+        }
+    };
+    let surface = pixbuf.create_surface(scale, None::<&gtk::gdk::Window>)?;
+    log::debug!(
+        "Loaded icon {:?} at {}x{}",
+        source,
+        pixbuf.width(),
+        pixbuf.height()
+    );
+
+    Some(Icon {
+        surface,
+        width: pixbuf.width() as f64 / scale as f64,
+        height: pixbuf.height() as f64 / scale as f64,
+    })
+}
@@ -0,0 +1,237 @@
+//! Lenient layout syntax.
+//!
+//! Hand-edited layouts may use `//` and `/* */` comments, trailing commas and
+//! Rust-style `\u{f023}` escapes (as written in the README). [`normalize`]
+//! rewrites such input into standard JSON without moving any token to a
+//! different line or byte column, so serde_json errors, whose columns count
+//! bytes, still point at the original text.
+
+use crate::error::{line_column, ConfigError, Location};
+
+/// Rewrite relaxed JSON into standard JSON, preserving line and byte column positions
+pub fn normalize(input: &str, origin: &str) -> Result<String, ConfigError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut output = String::with_capacity(input.len());
+    let mut i = 0;
+    // Bytes gained by lengthening escapes on the current line; taken back
+    // out of the next blanks
+    let mut surplus = 0;
+
+    let error = |offset: usize, message: &str| {
+        let (line, column) = line_column(input, offset);
+        ConfigError::Syntax {
+            location: Location::at(origin, line, column),
+            message: message.to_string(),
+        }
+    };
+
+    while i < chars.len() {
+        let (offset, c) = chars[i];
+        match c {
+            '"' => {
+                let (next, gained) =
+                    copy_string(&chars, i, &mut output).map_err(|(at, message)| {
+                        error(chars.get(at).map_or(input.len(), |&(o, _)| o), message)
+                    })?;
+                i = next;
+                surplus += gained;
+                continue;
+            }
+            ' ' | '\t' if surplus > 0 => surplus -= 1,
+            '\n' => {
+                surplus = 0;
+                output.push(c);
+            }
+            '/' if matches!(chars.get(i + 1), Some((_, '/' | '*'))) => {
+                i = skip_comment(&chars, i, &mut output)
+                    .ok_or_else(|| error(offset, "unterminated block comment"))?;
+                continue;
+            }
+            ',' if is_trailing_comma(&chars, i + 1) => output.push(' '),
+            _ => output.push(c),
+        }
+        i += 1;
+    }
+
+    Ok(output)
+}
+
+/// Copy a string literal starting at the opening quote, translating `\u{...}`
+/// escapes. Returns the index just past the closing quote and the number of
+/// bytes the copy came out longer than the original.
+fn copy_string(
+    chars: &[(usize, char)],
+    start: usize,
+    output: &mut String,
+) -> Result<(usize, usize), (usize, &'static str)> {
+    output.push('"');
+    // Bytes lost by shortening escapes, made up with spaces after the
+    // string; negative where escapes grew, as `\u{1}` does into `\u0001`
+    let mut deficit: isize = 0;
+    let mut i = start + 1;
+
+    while let Some(&(_, c)) = chars.get(i) {
+        match c {
+            '"' => {
+                output.push('"');
+                output.push_str(&" ".repeat(deficit.max(0) as usize));
+                return Ok((i + 1, (-deficit).max(0) as usize));
+            }
+            '\\' if matches!(chars.get(i + 1), Some((_, 'u')))
+                && matches!(chars.get(i + 2), Some((_, '{'))) =>
+            {
+                let close = chars[i + 3..]
+                    .iter()
+                    .position(|&(_, c)| c == '}')
+                    .map(|p| i + 3 + p)
+                    .ok_or((i, "unterminated \\u{...} escape"))?;
+                let hex: String = chars[i + 3..close].iter().map(|&(_, c)| c).collect();
+                let decoded = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| !hex.is_empty() && hex.len() <= 6)
+                    .and_then(char::from_u32)
+                    .ok_or((i, "invalid \\u{...} escape"))?;
+
+                let replacement = match decoded {
+                    '"' | '\\' | '\u{0}'..='\u{1f}' => format!("\\u{:04x}", decoded as u32),
+                    _ => decoded.to_string(),
+                };
+                let consumed = close + 1 - i;
+                // The escape is ASCII, so its characters are its bytes
+                deficit += consumed as isize - replacement.len() as isize;
+                output.push_str(&replacement);
+                i = close + 1;
+            }
+            '\\' => {
+                // Ordinary escape; copy it and the escaped character verbatim
+                output.push('\\');
+                if let Some(&(_, next)) = chars.get(i + 1) {
+                    output.push(next);
+                }
+                i += 2;
+            }
+            _ => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    // Let serde_json report the unterminated string
+    Ok((i, 0))
+}
+
+/// Blank out a comment starting at `start` byte for byte, keeping newlines.
+/// Returns the index just past the comment, or `None` if a block comment is unterminated.
+fn skip_comment(chars: &[(usize, char)], start: usize, output: &mut String) -> Option<usize> {
+    let block = chars[start + 1].1 == '*';
+    let mut i = start + 2;
+    output.push_str("  ");
+
+    while let Some(&(_, c)) = chars.get(i) {
+        if block && c == '*' && matches!(chars.get(i + 1), Some((_, '/'))) {
+            output.push_str("  ");
+            return Some(i + 2);
+        }
+        if !block && c == '\n' {
+            return Some(i);
+        }
+        match c {
+            '\n' => output.push('\n'),
+            _ => output.push_str(&" ".repeat(c.len_utf8())),
+        }
+        i += 1;
+    }
+
+    if block {
+        None
+    } else {
+        Some(i)
+    }
+}
+
+/// Check whether the next significant character after a comma closes an array or object
+fn is_trailing_comma(chars: &[(usize, char)], mut i: usize) -> bool {
+    while let Some(&(_, c)) = chars.get(i) {
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '/' if matches!(chars.get(i + 1), Some((_, '/'))) => {
+                while chars.get(i).is_some_and(|&(_, c)| c != '\n') {
+                    i += 1;
+                }
+            }
+            '/' if matches!(chars.get(i + 1), Some((_, '*'))) => {
+                i += 2;
+                while chars.get(i).is_some()
+                    && !(chars[i].1 == '*' && matches!(chars.get(i + 1), Some((_, '/'))))
+                {
+                    i += 1;
+                }
+                i += 2;
+            }
+            ']' | '}' => return true,
+            _ => return false,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_readme_example() {
+        let input = r#"[
+    // Lock the session
+    {
+        "label": "lock",
+        "text": "Lock",
+        "icon_char": "\u{f023}", /* Nerd Font lock */
+    },
+]"#;
+        let output = normalize(input, "<test>").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value[0]["icon_char"], "\u{f023}");
+        assert_eq!(value[0]["label"], "lock");
+    }
+
+    #[test]
+    fn test_normalize_keeps_positions() {
+        // serde_json reports the column of the stray `x` as written
+        let assert_column = |input: &str| {
+            let output = normalize(input, "<test>").unwrap();
+            let err = serde_json::from_str::<serde_json::Value>(&output).unwrap_err();
+            assert_eq!(err.column(), input.find('x').unwrap() + 1);
+        };
+        assert_column("[{\"icon_char\": \"\\u{f023}\", \"label\": x}]");
+        assert_column("[{\"icon_char\": \"\\u{f023}\" /* \u{f023} */, \"label\": x}]");
+
+        // A control character comes out one column longer than written
+        assert_column("[{\"icon_char\": \"\\u{1}\", \"label\": x}]");
+        let output = normalize("[\"\\u{1}\"]", "<test>").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value[0], "\u{1}");
+
+        let input = "[1, // one\n 2,\n]";
+        let output = normalize(input, "<test>").unwrap();
+        assert_eq!(output, "[1,       \n 2 \n]");
+    }
+
+    #[test]
+    fn test_normalize_leaves_strings_alone() {
+        let input = r#"["a // b", "c, ]", "\"\\u{41}\"", "\\n"]"#;
+        let output = normalize(input, "<test>").unwrap();
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn test_normalize_errors() {
+        let err = normalize("[\n  \"\\u{zz}\"]", "layout").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:4: syntax error: invalid \\u{...} escape"
+        );
+        assert!(normalize("[] /* open", "layout").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! List layout: a single column of rows, each an icon followed by its label.
+
+use crate::menu_layout::{
+    cycle, draw_icon, draw_label, fill_button, paint_order, rounded_rect_path, MenuButton,
+    MenuLayout, Nav, LABEL_PADDING,
+};
+use crate::settings::Settings;
+use gtk::gdk::cairo::Context;
+use gtk::pango;
+
+/// Rows `width` by `row_height` pixels, the first with its corner at (x, y)
+pub struct ListLayout {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub row_height: f64,
+    pub spacing: f64,
+}
+
+impl ListLayout {
+    /// A list of `count` buttons in a `width`x`height` window on a monitor
+    /// with `pixels_per_mm`, with the point of the list matching the anchor
+    /// placed on the anchor
+    pub fn new(
+        count: usize,
+        width: f64,
+        height: f64,
+        pixels_per_mm: f64,
+        settings: &Settings,
+    ) -> Self {
+        let list_width = settings.list_width.pixels(pixels_per_mm);
+        let row_height = settings.row_height.pixels(pixels_per_mm);
+        let rows = count.max(1) as f64;
+        let list_height = rows * row_height + (rows - 1.0) * settings.cell_spacing;
+        let (anchor_x, anchor_y) = settings.anchor.position(width, height);
+        let (fraction_x, fraction_y) = settings.anchor.fractions();
+
+        Self {
+            x: anchor_x - list_width * fraction_x,
+            y: anchor_y - list_height * fraction_y,
+            width: list_width,
+            row_height,
+            spacing: settings.cell_spacing,
+        }
+    }
+
+    /// Top of the row of button `index`
+    fn row_top(&self, index: usize) -> f64 {
+        self.y + index as f64 * (self.row_height + self.spacing)
+    }
+}
+
+impl MenuLayout for ListLayout {
+    /// A row, grown around its center
+    fn button_path(&self, cr: &Context, index: usize, scale: f64, settings: &Settings) {
+        let center_y = self.row_top(index) + self.row_height / 2.0;
+        let (width, height) = (self.width * scale, self.row_height * scale);
+        rounded_rect_path(
+            cr,
+            self.x + (self.width - width) / 2.0,
+            center_y - height / 2.0,
+            width,
+            height,
+            settings.corner_radius,
+        );
+    }
+
+    fn draw(
+        &self,
+        cr: &Context,
+        buttons: &[MenuButton],
+        hover_button: i32,
+        scales: &[f64],
+        settings: &Settings,
+    ) {
+        for i in paint_order(buttons.len(), hover_button) {
+            let button = &buttons[i];
+            let scale = scales.get(i).copied().unwrap_or(1.0);
+            let center_y = self.row_top(i) + self.row_height / 2.0;
+            let (width, height) = (self.width * scale, self.row_height * scale);
+            let left = self.x + (self.width - width) / 2.0;
+
+            self.button_path(cr, i, scale, settings);
+            let foreground = fill_button(cr, button, i as i32 == hover_button);
+
+            // The icon sits in a square at the start of the row, the label
+            // fills the rest; rows always show their label
+            draw_icon(
+                cr,
+                button,
+                left + height / 2.0,
+                center_y,
+                button.icon_size.min(self.row_height) * scale,
+                foreground,
+            );
+            draw_label(
+                cr,
+                button,
+                left + height,
+                center_y + button.label_font_size / 3.0,
+                (width - height - LABEL_PADDING).max(0.0),
+                pango::Alignment::Left,
+                foreground,
+                settings,
+            );
+        }
+    }
+
+    /// Up and down move between rows, stopping at the ends
+    fn neighbor(&self, from: Option<usize>, nav: Nav, count: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+        let Some(from) = from else {
+            return Some(0);
+        };
+
+        Some(match nav {
+            Nav::Next | Nav::Previous => cycle(from, nav, count),
+            Nav::Up if from > 0 => from - 1,
+            Nav::Down if from + 1 < count => from + 1,
+            _ => from,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbor() {
+        let list = ListLayout {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            row_height: 10.0,
+            spacing: 0.0,
+        };
+        let neighbor = |from, nav| list.neighbor(Some(from), nav, 3);
+
+        assert_eq!(list.neighbor(None, Nav::Up, 3), Some(0));
+        assert_eq!(list.neighbor(None, Nav::Up, 0), None);
+
+        assert_eq!(neighbor(0, Nav::Down), Some(1));
+        assert_eq!(neighbor(2, Nav::Up), Some(1));
+        assert_eq!(neighbor(0, Nav::Up), Some(0));
+        assert_eq!(neighbor(2, Nav::Down), Some(2));
+        assert_eq!(neighbor(1, Nav::Left), Some(1));
+
+        assert_eq!(neighbor(2, Nav::Next), Some(0));
+        assert_eq!(neighbor(0, Nav::Previous), Some(2));
+    }
+}
@@ -1,57 +1,250 @@
+mod check;
 mod circular_layout;
+mod cli;
+mod color;
 mod config;
-
-use anyhow::Result;
-use circular_layout::{draw_circular_layout, get_clicked_button, CircularButton};
-use config::{load_config, parse_color_with_alpha, Button};
+mod css;
+mod error;
+mod format;
+mod grid_layout;
+mod icons;
+mod lenient;
+mod list_layout;
+mod menu_layout;
+mod nerd_fonts;
+mod paths;
+mod resolve;
+mod settings;
+mod style;
+mod theme;
+
+use circular_layout::{Ring, RingLayout};
+use cli::{Args, LayoutSource};
+use color::{parse_color, Color, DEFAULT_COLOR, DEFAULT_HOVER_COLOR, MIN_CONTRAST};
+use config::{parse_config, parse_layout, serialize_config, Button, Layout, Source};
+use css::{overlay_color, wedge_style, WedgeStyle, OVERLAY_CLASS};
+use error::{ConfigError, Location};
+use format::LayoutFormat;
+use grid_layout::GridLayout;
 use gtk::prelude::*;
 use gtk::{DrawingArea, EventBox, Window, WindowType};
+use icons::{load_icon_file, load_theme_icon};
+use list_layout::ListLayout;
+use menu_layout::{draw_menu, Frame, MenuButton, MenuLayout, MenuView, Nav};
+use resolve::{resolve_layout, ParseFn};
+use settings::{LayoutKind, Settings, SubmenuMode};
 use std::cell::RefCell;
-use std::f64::consts::PI;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::rc::Rc;
 use std::time::Duration;
+use style::style_layout;
+use theme::load_theme;
 
 struct AppState {
     /// Stack of menu levels: root menu at index 0, submenus stacked on top
     menu_stack: Vec<Vec<Button>>,
-    hover_button: i32,
+    /// Index of the button each submenu was opened from, in the level below it
+    opened_from: Vec<usize>,
+    /// How each menu level is laid out
+    layouts: Vec<LayoutKind>,
+    settings: Settings,
+    /// Hovered button as its menu level and index
+    hover: Option<(usize, usize)>,
     animation_progress: f64, // 0.0 to 1.0 for slide-in
     start_x: f64,
     start_y: f64,
+    /// Whether a stylesheet was loaded to style the ring
+    use_css: bool,
+    /// The buttons of each menu level as drawn, built on the level's first
+    /// frame rather than on every one
+    buttons: Vec<Vec<MenuButton>>,
+    /// Scale factor and pixels per millimetre `buttons` were built for
+    built_for: (i32, f64),
+    /// Where the last frame drew the menu, for input to hit-test against;
+    /// `None` until the first frame and after the menu levels change
+    frame: Option<Frame>,
 }
 
 impl AppState {
-    fn new(buttons: Vec<Button>) -> Self {
+    fn new(layout: Layout) -> Self {
         Self {
-            menu_stack: vec![buttons],
-            hover_button: -1,
+            menu_stack: vec![with_default_icons(layout.buttons)],
+            opened_from: Vec::new(),
+            layouts: vec![layout.settings.layout],
+            settings: layout.settings,
+            hover: None,
             animation_progress: 0.0,
             start_x: 0.0,
             start_y: 0.0,
+            use_css: false,
+            buttons: Vec::new(),
+            built_for: (1, FALLBACK_PIXELS_PER_MM),
+            frame: None,
         }
     }
 
-    /// Get the current menu (top of stack)
-    fn current_menu(&self) -> &Vec<Button> {
-        self.menu_stack
-            .last()
-            .expect("Menu stack should never be empty")
+    /// Relative wedge sizes of the buttons of a menu level
+    fn weights(&self, level: usize) -> Vec<f64> {
+        self.menu_stack[level]
+            .iter()
+            .map(|button| button.weight.unwrap_or(1.0))
+            .collect()
+    }
+
+    /// The menu levels on screen: the current one, or when submenus expand
+    /// outward, the rings it expands from as well. A grid or list level
+    /// always replaces what was shown before it.
+    fn visible_levels(&self) -> Range<usize> {
+        let top = self.menu_stack.len() - 1;
+        let is_ring = |level: usize| self.layouts[level] == LayoutKind::Ring;
+        let mut start = top;
+        if self.settings.submenu_mode == SubmenuMode::Expand {
+            while start > 0 && is_ring(start) && is_ring(start - 1) {
+                start -= 1;
+            }
+        }
+        start..top + 1
+    }
+
+    /// The layouts of the visible levels in a `width`x`height` window on a
+    /// monitor with `pixels_per_mm`, innermost first
+    fn layouts(
+        &self,
+        width: f64,
+        height: f64,
+        pixels_per_mm: f64,
+    ) -> Vec<(usize, Box<dyn MenuLayout>)> {
+        let settings = &self.settings;
+        let (center_x, center_y) = settings.anchor.position(width, height);
+        let mut parent: Option<(usize, Ring)> = None;
+        let mut parent_spans = Vec::new();
+
+        self.visible_levels()
+            .map(|level| {
+                let count = self.menu_stack[level].len();
+                let layout: Box<dyn MenuLayout> = match self.layouts[level] {
+                    LayoutKind::Ring => {
+                        let ring = match parent {
+                            Some((parent, parent_ring)) => {
+                                let weights = self.weights(parent);
+                                parent_ring.child(
+                                    parent_spans[self.opened_from[parent]],
+                                    weights.iter().sum(),
+                                    self.weights(level).iter().sum(),
+                                    settings,
+                                )
+                            }
+                            None => Ring::root(
+                                settings.ring_radius(width, height, pixels_per_mm),
+                                settings,
+                            ),
+                        };
+                        let layout = RingLayout::new(
+                            center_x,
+                            center_y,
+                            ring,
+                            parent.is_none(),
+                            &self.weights(level),
+                        );
+                        parent = Some((level, ring));
+                        parent_spans.clone_from(&layout.spans);
+                        Box::new(layout)
+                    }
+                    LayoutKind::Grid => Box::new(GridLayout::new(
+                        count,
+                        width,
+                        height,
+                        pixels_per_mm,
+                        settings,
+                    )),
+                    LayoutKind::List => Box::new(ListLayout::new(
+                        count,
+                        width,
+                        height,
+                        pixels_per_mm,
+                        settings,
+                    )),
+                };
+                (level, layout)
+            })
+            .collect()
+    }
+
+    /// The button drawn under (x, y) in the last frame, as its menu level
+    /// and index
+    fn button_at(&self, (x, y): (f64, f64)) -> Option<(usize, usize)> {
+        self.frame.as_ref()?.button_at(x, y, &self.settings)
+    }
+
+    /// Move the keyboard selection within the hovered level, or the current
+    /// one when nothing is hovered
+    fn navigate(&mut self, nav: Nav) {
+        let level = self
+            .hover
+            .map_or(self.menu_stack.len() - 1, |(level, _)| level);
+        let from = self.hover.map(|(_, index)| index);
+        if let Some(frame) = &self.frame {
+            self.hover = frame.neighbor(level, from, nav).map(|index| (level, index));
+        }
     }
 
-    /// Navigate into a submenu
-    fn push_submenu(&mut self, submenu: Vec<Button>) {
-        self.menu_stack.push(submenu);
-        self.hover_button = -1;
+    /// Open the submenu of button `index` in menu `level`, closing any
+    /// submenus above that level first. Opening the submenu that is
+    /// already open closes it instead.
+    fn open_submenu(&mut self, level: usize, index: usize, button: &Button) {
+        let already_open = self.opened_from.get(level) == Some(&index);
+        self.menu_stack.truncate(level + 1);
+        self.opened_from.truncate(level);
+        self.layouts.truncate(level + 1);
+        self.buttons.truncate(level + 1);
+        self.frame = None;
+        if already_open {
+            self.hover = None;
+        } else {
+            self.push_submenu(index, button);
+        }
+    }
+
+    /// Navigate into a submenu, laid out as the button asks or as the
+    /// settings default
+    fn push_submenu(&mut self, index: usize, button: &Button) {
+        self.menu_stack
+            .push(with_default_icons(button.children.clone()));
+        self.opened_from.push(index);
+        self.layouts
+            .push(button.layout.unwrap_or(self.settings.layout));
+        self.frame = None;
+        self.hover = None;
         self.animation_progress = 0.0; // Start fade-in from 0
     }
 
     /// Navigate back to parent menu
     fn pop_submenu(&mut self) {
         if self.menu_stack.len() > 1 {
+            // An expanded parent ring is already showing
+            let parent_shown = self.visible_levels().start + 1 < self.menu_stack.len();
             self.menu_stack.pop();
-            self.hover_button = -1;
-            self.animation_progress = 0.0; // Start fade-in from 0
+            self.opened_from.pop();
+            self.layouts.pop();
+            self.buttons.truncate(self.menu_stack.len());
+            self.frame = None;
+            self.hover = None;
+            if !parent_shown {
+                self.animation_progress = 0.0; // Start fade-in from 0
+            }
+        }
+    }
+
+    /// Opacity of a menu level's ring: the newest ring fades in, rings
+    /// below it are already shown
+    fn level_opacity(&self, level: usize) -> f64 {
+        if level + 1 < self.menu_stack.len() {
+            1.0
+        } else {
+            self.animation_progress
         }
     }
 
@@ -61,86 +254,272 @@ impl AppState {
     }
 }
 
+/// Directories searched for `<label>.png` or `.svg` when a button names no icon
+const ICON_DIRS: &[&str] = &[
+    "./icons",
+    "/usr/local/share/snmenu/icons",
+    "/usr/share/snmenu/icons",
+];
+
+/// Give buttons with neither an icon_path nor an icon_name the image named
+/// after their label in `ICON_DIRS`, if there is one. Menu levels go through
+/// this once, as they are loaded or pushed, not on every frame.
+fn with_default_icons(mut buttons: Vec<Button>) -> Vec<Button> {
+    for button in &mut buttons {
+        if button.icon_path.is_some() || button.icon_name.is_some() {
+            continue;
+        }
+        button.icon_path = ICON_DIRS
+            .iter()
+            .flat_map(|dir| ["png", "svg"].map(|ext| format!("{}/{}.{}", dir, button.label, ext)))
+            .find(|path| Path::new(path).is_file());
+    }
+    buttons
+}
+
 fn main() {
     env_logger::builder()
         .filter_level(log::LevelFilter::Info)
         .try_init()
         .ok();
 
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli::Command::Run(args)) => args,
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(cli::Command::Version) => {
+            println!("snmenu {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(e) => {
+            eprintln!("snmenu: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if args.check {
+        std::process::exit(check(&args));
+    }
+
+    if let Some(output) = &args.convert {
+        std::process::exit(convert(&args, output));
+    }
+
+    // Load the layout before touching GTK so configuration errors exit
+    // cleanly instead of leaving an empty main loop running
+    let layout = match load_layout(&args, true) {
+        Ok((layout, _)) => layout,
+        Err(e) => {
+            eprintln!("snmenu: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     gtk::init().expect("Failed to initialize GTK");
-    build_ui();
+    build_ui(&args, layout);
     gtk::main();
 }
 
-fn get_layout_path() -> Result<String> {
-    // Try SNMENU_CONFIG environment variable first
-    if let Ok(config_path) = std::env::var("SNMENU_CONFIG") {
-        if std::path::Path::new(&config_path).exists() {
-            return Ok(config_path);
+/// Candidate layout file names inside each configuration directory
+const LAYOUT_NAMES: &[&str] = &[
+    "layout",
+    "layout.json",
+    "layout.toml",
+    "layout.yaml",
+    "layout.yml",
+];
+
+/// Lint the layout and print every problem found, returning the exit code
+fn check(args: &Args) -> i32 {
+    let (layout, errors) = match load_layout(args, false) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("snmenu: {}", e);
+            return 1;
         }
+    };
+
+    // Names and includes that could not be resolved come first, with the
+    // place they are written
+    for error in &errors {
+        println!("{}", error);
+    }
+    let issues = check::check_layout(&layout);
+    for issue in &issues {
+        println!("{}", issue);
     }
 
-    // Try XDG_CONFIG_HOME next
-    if let Ok(xdg_config) = std::env::var("XDG_CONFIG_HOME") {
-        let path = format!("{}/cpmenu/layout", xdg_config);
-        if std::path::Path::new(&path).exists() {
-            return Ok(path);
-        }
+    let count = errors.len() + issues.len();
+    if count == 0 {
+        println!("No problems found");
+        0
+    } else {
+        eprintln!("{} problem(s) found", count);
+        1
     }
+}
 
-    // Try ~/.config/cpmenu/layout
-    if let Ok(home) = std::env::var("HOME") {
-        let path = format!("{}/.config/cpmenu/layout", home);
-        if std::path::Path::new(&path).exists() {
-            return Ok(path);
+/// Parse the layout text and resolve its includes, named menu references
+/// and theme colors, returning the names and includes that could not be
+/// resolved along with the layout.
+///
+/// `theme` comes from the command line and overrides `settings.theme`;
+/// a theme path in the settings is relative to the layout file.
+fn load_source(
+    source: &Source,
+    parse: ParseFn,
+    theme: Option<&str>,
+) -> Result<(Layout, Vec<ConfigError>), ConfigError> {
+    let layout = parse(&source.content, &source.origin, source.format)?;
+
+    let palette = match (theme, &layout.settings.theme) {
+        (Some(theme), _) => Some(load_theme(theme, Path::new(""))?),
+        (None, Some(theme)) => {
+            let base = source.path.as_deref().and_then(Path::parent);
+            Some(load_theme(theme, base.unwrap_or(Path::new("")))?)
         }
-    }
+        (None, None) => None,
+    };
 
-    // Try system paths
-    if std::path::Path::new("/etc/cpmenu/layout").exists() {
-        return Ok("/etc/cpmenu/layout".to_string());
-    }
+    Ok(resolve_layout(layout, source, parse, palette.as_ref()))
+}
 
-    if std::path::Path::new("/usr/local/etc/cpmenu/layout").exists() {
-        return Ok("/usr/local/etc/cpmenu/layout".to_string());
+/// Read the layout text selected on the command line or by the search paths
+fn read_layout(args: &Args) -> Result<Source, ConfigError> {
+    match &args.layout {
+        Some(LayoutSource::File(path)) => Source::from_file(path),
+        Some(LayoutSource::Stdin) => {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|source| ConfigError::Io {
+                    origin: "<stdin>".to_string(),
+                    source,
+                })?;
+            Ok(Source::new(&content, "<stdin>", LayoutFormat::Json))
+        }
+        Some(LayoutSource::Inline(json)) => {
+            Ok(Source::new(json, "<layout-json>", LayoutFormat::Json))
+        }
+        None => Source::from_file(&get_layout_path()?),
     }
-
-    Err(anyhow::anyhow!("Failed to find layout file"))
 }
 
-fn get_css_path() -> Option<String> {
-    // Try XDG_CONFIG_HOME first
-    if let Ok(xdg_config) = std::env::var("XDG_CONFIG_HOME") {
-        let path = format!("{}/cpmenu/style.css", xdg_config);
-        if std::path::Path::new(&path).exists() {
-            return Some(path);
+/// Load the layout from the command line source, falling back to the search paths.
+///
+/// With `validate` unset, values such as colors are left for `--check` to
+/// report, style defaults stay where they are written instead of being
+/// applied to the buttons, and names and includes that cannot be resolved
+/// are returned along with the layout rather than failing the load.
+fn load_layout(args: &Args, validate: bool) -> Result<(Layout, Vec<ConfigError>), ConfigError> {
+    let parse: ParseFn = if validate { parse_config } else { parse_layout };
+    let source = read_layout(args)?;
+    let theme = args.theme.as_deref();
+    let mut problems = Vec::new();
+    let mut load = |source: &Source| -> Result<Layout, ConfigError> {
+        let (mut layout, errors) = load_source(source, parse, theme)?;
+        if validate {
+            if let Some(error) = errors.into_iter().next() {
+                return Err(error);
+            }
+            style_layout(&mut layout);
+        } else {
+            problems.extend(errors);
         }
+        Ok(layout)
+    };
+    let layout = load(&source)?;
+
+    if !args.merge {
+        return Ok((layout, problems));
     }
 
-    // Try ~/.config/cpmenu/style.css
-    if let Ok(home) = std::env::var("HOME") {
-        let path = format!("{}/.config/cpmenu/style.css", home);
-        if std::path::Path::new(&path).exists() {
-            return Some(path);
+    // Layer the loaded layout on top of the system-wide one, unless that is
+    // the file we just loaded
+    match paths::find_system_config_file(LAYOUT_NAMES) {
+        Some(system_path) if Some(&system_path) != source.path.as_ref() => {
+            log::info!("Merging layout on top of {}", system_path.display());
+            let base = load(&Source::from_file(&system_path)?)?;
+            let merged = base.merge(layout);
+            // Each file's settings were valid alone, but their combination
+            // may not be, e.g. a min_radius from one above a max_radius
+            // from the other
+            if validate {
+                merged
+                    .settings
+                    .validate()
+                    .map_err(|(name, message)| ConfigError::Schema {
+                        location: Location::new(&source.origin),
+                        message: format!("settings.{} {} once merged", name, message),
+                    })?;
+            }
+            Ok((merged, problems))
         }
+        _ => Ok((layout, problems)),
     }
+}
 
-    // Try system paths
-    if std::path::Path::new("/etc/cpmenu/style.css").exists() {
-        return Some("/etc/cpmenu/style.css".to_string());
+/// Write the layout to `output` in the format given by its extension.
+///
+/// Includes and named menus are kept as they are rather than expanded.
+fn convert(args: &Args, output: &Path) -> i32 {
+    let layout = match read_layout(args)
+        .and_then(|source| parse_config(&source.content, &source.origin, source.format))
+    {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("snmenu: {}", e);
+            return 1;
+        }
+    };
+
+    let format = LayoutFormat::from_path(output);
+    let text = match serialize_config(&layout, format) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("snmenu: failed to convert layout: {}", e);
+            return 1;
+        }
+    };
+
+    if let Err(e) = std::fs::write(output, text) {
+        eprintln!("snmenu: {}: {}", output.display(), e);
+        return 1;
     }
+    0
+}
 
-    if std::path::Path::new("/usr/local/etc/cpmenu/style.css").exists() {
-        return Some("/usr/local/etc/cpmenu/style.css".to_string());
+fn get_layout_path() -> Result<PathBuf, ConfigError> {
+    // Try SNMENU_CONFIG environment variable first
+    if let Ok(config_path) = std::env::var("SNMENU_CONFIG") {
+        if std::path::Path::new(&config_path).exists() {
+            return Ok(config_path.into());
+        }
+        log::warn!("SNMENU_CONFIG points to missing file: {}", config_path);
     }
 
-    None
+    paths::find_config_file(LAYOUT_NAMES).ok_or_else(|| {
+        let searched: Vec<String> = paths::config_dirs(true)
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect();
+        ConfigError::NoLayout {
+            searched: searched.join(", "),
+        }
+    })
+}
+
+fn get_css_path() -> Option<PathBuf> {
+    paths::find_config_file(&["style.css"])
 }
 
-fn load_css(css_path: Option<String>) {
+/// Install the stylesheet for the screen, returning whether it loaded
+fn load_css(css_path: Option<PathBuf>) -> bool {
     if let Some(path) = css_path {
         let provider = gtk::CssProvider::new();
-        match provider.load_from_path(&path) {
+        match provider.load_from_path(&path.to_string_lossy()) {
             Ok(_) => {
                 let screen = gdk::Screen::default().expect("Failed to get screen");
                 gtk::StyleContext::add_provider_for_screen(
@@ -148,19 +527,153 @@ fn load_css(css_path: Option<String>) {
                     &provider,
                     gtk::STYLE_PROVIDER_PRIORITY_USER,
                 );
+                return true;
             }
             Err(e) => {
-                log::warn!("Failed to load CSS: {:?}", e);
+                log::warn!("Failed to load CSS from {}: {:?}", path.display(), e);
             }
         }
     }
+    false
+}
+
+/// Logical pixels per millimetre assumed when the monitor does not report
+/// its physical size: 96 DPI
+const FALLBACK_PIXELS_PER_MM: f64 = 96.0 / 25.4;
+
+/// Logical pixels per millimetre on the monitor showing `widget`
+fn pixels_per_mm(widget: &impl IsA<gtk::Widget>) -> f64 {
+    let monitor = widget
+        .window()
+        .and_then(|window| widget.display().monitor_at_window(&window));
+    monitor
+        .and_then(|monitor| {
+            // Projectors and virtual outputs report no physical size
+            let width_mm = monitor.width_mm();
+            (width_mm > 0).then(|| monitor.geometry().width() as f64 / width_mm as f64)
+        })
+        .unwrap_or(FALLBACK_PIXELS_PER_MM)
+}
+
+/// Everything needed to draw `btn` on a monitor with `pixels_per_mm`: its
+/// colors parsed, its sizes in pixels and its icon loaded, with the settings
+/// and, when `use_css` is set, the stylesheet filling in what the button
+/// leaves out
+fn menu_button(
+    btn: &Button,
+    settings: &Settings,
+    use_css: bool,
+    widget: &DrawingArea,
+    pixels_per_mm: f64,
+) -> MenuButton {
+    // The stylesheet's button rules for this label, if one is loaded
+    let css = if use_css {
+        wedge_style(widget, &btn.label)
+    } else {
+        WedgeStyle::default()
+    };
+
+    // Use custom colors if provided, then the stylesheet's, otherwise
+    // the default neutral blues. Colors were validated on load; an
+    // alpha in the color itself overrides the configured opacity.
+    let parse = |color: &Option<String>| color.as_deref().and_then(|color| parse_color(color).ok());
+    let (color, color_alpha) = match parse(&btn.color).or(css.normal.background) {
+        Some(color) => (color, settings.wedge_alpha),
+        None => (DEFAULT_COLOR, settings.default_wedge_alpha),
+    };
+    // Without a hover_color, derive one from the base color
+    let hover = parse(&btn.hover_color)
+        .or_else(|| parse(&btn.color).map(|color| color.hover_variant()))
+        .or(css.hover.background)
+        .or_else(|| css.normal.background.map(|color| color.hover_variant()));
+    let (hover, hover_alpha) = match hover {
+        Some(hover) => (hover, settings.hover_alpha),
+        None => (DEFAULT_HOVER_COLOR, settings.default_hover_alpha),
+    };
+
+    // Icons and labels use the configured foreground, then the
+    // stylesheet's, or whichever of white and black contrasts with
+    // the wedge as drawn
+    let foreground = parse(&btn.foreground);
+    let foreground_on = |wedge: &Color, alpha: f64, styled: Option<Color>| {
+        foreground
+            .or(styled)
+            .unwrap_or_else(|| {
+                wedge
+                    .over_black(wedge.alpha.unwrap_or(alpha))
+                    .foreground(MIN_CONTRAST)
+            })
+            .rgba(1.0)
+    };
+    // A border from the stylesheet replaces the configured one
+    let (border_width, border, hover_border) = match css.normal.border {
+        Some(border) if css.border_width > 0.0 => {
+            (css.border_width, border, css.hover.border.unwrap_or(border))
+        }
+        _ => (
+            settings.border_width,
+            settings.border_color(),
+            settings.border_color(),
+        ),
+    };
+
+    // Image icons are decoded at the fully hovered size, so the
+    // animation only ever scales them down
+    let icon_size = btn
+        .icon_size
+        .unwrap_or(settings.icon_size)
+        .pixels(pixels_per_mm);
+    let pixels = ((icon_size * settings.hover_scale.max(1.0)).ceil() as u32).max(1);
+    let scale_factor = widget.scale_factor();
+
+    // Try to find icon in order:
+    // 1. icon_path from config, or found in the default icon
+    //    directories when the level was pushed
+    // 2. icon_name from the icon theme
+    // and otherwise draw the icon text
+    let image = btn
+        .icon_path
+        .as_deref()
+        .and_then(|path| load_icon_file(path, pixels, scale_factor))
+        .or_else(|| {
+            btn.icon_name
+                .as_deref()
+                .and_then(|name| load_theme_icon(name, pixels, scale_factor))
+        });
+
+    MenuButton {
+        label: btn.text.clone(),
+        action: btn.action.clone(),
+        color: color.rgba(color_alpha),
+        hover_color: hover.rgba(hover_alpha),
+        foreground: foreground_on(&color, color_alpha, css.normal.foreground),
+        hover_foreground: foreground_on(
+            &hover,
+            hover_alpha,
+            css.hover.foreground.or(css.normal.foreground),
+        ),
+        border_width,
+        border_color: border.rgba(1.0),
+        hover_border_color: hover_border.rgba(1.0),
+        image,
+        icon: btn.icon.clone(),
+        icon_font: btn.icon_font.clone().unwrap_or(settings.icon_font.clone()),
+        icon_size,
+        label_font: btn
+            .font
+            .clone()
+            .or(css.font)
+            .unwrap_or(settings.label_font.clone()),
+        label_font_size: settings.label_font_size.pixels(pixels_per_mm),
+        show_label: btn.show_label.unwrap_or(false),
+    }
 }
 
 fn get_mouse_position() -> (f64, f64) {
     use std::process::Command;
 
     // Use hyprctl to get mouse position from Hyprland
-    if let Ok(output) = Command::new("hyprctl").args(&["cursorpos"]).output() {
+    if let Ok(output) = Command::new("hyprctl").args(["cursorpos"]).output() {
         if let Ok(output_str) = String::from_utf8(output.stdout) {
             let coords: Vec<&str> = output_str.trim().split(',').collect();
             if coords.len() == 2 {
@@ -189,34 +702,18 @@ fn get_mouse_position() -> (f64, f64) {
     (0.0, 0.0)
 }
 
-fn build_ui() {
-    // Load layout
-    let layout_path = match get_layout_path() {
-        Ok(path) => path,
-        Err(e) => {
-            log::error!("Failed to find layout: {}", e);
-            return;
-        }
-    };
-
-    let buttons = match load_config(&layout_path) {
-        Ok(buttons) => buttons,
-        Err(e) => {
-            log::error!("Failed to load configuration: {}", e);
-            return;
-        }
-    };
-
-    // Load CSS
-    let css_path = get_css_path();
-    load_css(css_path);
+fn build_ui(args: &Args, layout: Layout) {
+    // Load CSS (explicit path first, then the search paths)
+    let css_path = args.css.clone().or_else(get_css_path);
+    let use_css = load_css(css_path);
 
     // Get mouse position for slide-in animation
     let (mouse_x, mouse_y) = get_mouse_position();
 
-    let state = Rc::new(RefCell::new(AppState::new(buttons)));
+    let state = Rc::new(RefCell::new(AppState::new(layout)));
     state.borrow_mut().start_x = mouse_x;
     state.borrow_mut().start_y = mouse_y;
+    state.borrow_mut().use_css = use_css;
 
     // Create main window
     let window = Window::new(WindowType::Toplevel);
@@ -228,6 +725,7 @@ fn build_ui() {
     let drawing_area = DrawingArea::new();
     drawing_area.set_hexpand(true);
     drawing_area.set_vexpand(true);
+    drawing_area.style_context().add_class(OVERLAY_CLASS);
     drawing_area.set_events(
         gdk::EventMask::POINTER_MOTION_MASK
             | gdk::EventMask::BUTTON_PRESS_MASK
@@ -240,7 +738,7 @@ fn build_ui() {
 
         // Update animation progress (slide in over ~6 frames)
         if state.animation_progress < 1.0 {
-            state.animation_progress += 0.12;
+            state.animation_progress += state.settings.fade_in_speed;
             if state.animation_progress > 1.0 {
                 state.animation_progress = 1.0;
             }
@@ -249,9 +747,8 @@ fn build_ui() {
         let width = widget.allocated_width() as f64;
         let height = widget.allocated_height() as f64;
 
-        // Calculate animation offset (slide from button to center)
-        let center_x = width / 2.0;
-        let center_y = height / 2.0;
+        // Calculate animation offset (slide from button to the ring's anchor)
+        let (center_x, center_y) = state.settings.anchor.position(width, height);
         let progress = state.animation_progress;
 
         // Only slide for root menu, fade only for submenus
@@ -262,111 +759,74 @@ fn build_ui() {
             let y = state.start_y + (center_y - state.start_y) * progress;
             (x, y)
         };
-        let opacity = progress; // Fade in
-
-        // Apply animation to the menu itself
-        let _ = cr.save();
-        cr.translate(anim_x - center_x, anim_y - center_y);
-
-        // Set semi-transparent background with opacity
-        cr.set_source_rgba(0.0, 0.0, 0.0, 0.35 * opacity);
+        // Fade in; the overlay with the innermost ring on screen
+        let opacity = state.level_opacity(state.visible_levels().start);
+
+        // Set semi-transparent background with opacity, black unless the
+        // stylesheet gives the drawing area a background-color
+        let settings = &state.settings;
+        let overlay = state
+            .use_css
+            .then(|| overlay_color(widget))
+            .flatten()
+            .unwrap_or(Color::BLACK)
+            .rgba(settings.overlay_alpha);
+        cr.set_source_rgba(overlay.0, overlay.1, overlay.2, overlay.3 * opacity);
         let _ = cr.paint();
 
-        // Convert buttons to CircularButton format
-        let circular_buttons: Vec<CircularButton> = state
-            .current_menu()
-            .iter()
-            .map(|btn| {
-                // Use custom colors if provided, otherwise use defaults
-                let (base_color, hover_color) =
-                    if let (Some(color), Some(hover)) = (&btn.color, &btn.hover_color) {
-                        // Custom colors from config
-                        (
-                            parse_color_with_alpha(color, 0.8 * opacity),
-                            parse_color_with_alpha(hover, 0.9 * opacity),
-                        )
-                    } else {
-                        // Default neutral blue colors for any button type
-                        (
-                            parse_color_with_alpha("#81A1C1", 0.35 * opacity),
-                            parse_color_with_alpha("#5E81AC", 0.55 * opacity),
-                        )
-                    };
-
-                // Try to find icon in order:
-                // 1. Custom icon_path from config
-                // 2. Default icon search paths
-                let icon_path = if let Some(custom_path) = &btn.icon_path {
-                    if std::path::Path::new(custom_path).exists() {
-                        Some(custom_path.clone())
-                    } else {
-                        log::warn!("Custom icon not found: {}", custom_path);
-                        None
-                    }
-                } else {
-                    [
-                        format!("./icons/{}.png", btn.label),
-                        format!("/usr/local/share/cpmenu/icons/{}.png", btn.label),
-                        format!("/usr/share/cpmenu/icons/{}.png", btn.label),
-                    ]
-                    .iter()
-                    .find(|p| std::path::Path::new(p).exists())
-                    .cloned()
-                };
+        // Build the buttons of levels shown for the first time; icons and
+        // sizes depend on the monitor, so moving to another rebuilds all
+        let monitor = (widget.scale_factor(), pixels_per_mm(widget));
+        if state.built_for != monitor {
+            state.built_for = monitor;
+            state.buttons.clear();
+        }
+        while state.buttons.len() < state.menu_stack.len() {
+            let level = state.buttons.len();
+            let buttons = state.menu_stack[level]
+                .iter()
+                .map(|btn| menu_button(btn, &state.settings, state.use_css, widget, monitor.1))
+                .collect();
+            state.buttons.push(buttons);
+        }
 
-                CircularButton {
-                    label: btn.text.clone(),
-                    action: btn.action.clone(),
-                    color: base_color,
-                    hover_color,
-                    icon_path,
-                    icon_char: btn.icon_char,
-                    show_label: btn.show_label,
-                }
+        let views: Vec<MenuView> = state
+            .layouts(width, height, monitor.1)
+            .into_iter()
+            .map(|(level, layout)| MenuView {
+                level,
+                layout,
+                buttons: &state.buttons[level],
+                opacity: state.level_opacity(level),
+                hover_button: match state.hover {
+                    Some((hover_level, index)) if hover_level == level => index as i32,
+                    _ => -1,
+                },
             })
             .collect();
 
-        // Draw circular layout
-        draw_circular_layout(
-            cr,
-            width as i32,
-            height as i32,
-            &circular_buttons,
-            state.hover_button,
-        );
-
-        let _ = cr.restore();
+        // Draw every visible menu level, moved by the slide-in, and keep
+        // where it went for the input handlers
+        let offset = (anim_x - center_x, anim_y - center_y);
+        let frame = draw_menu(cr, views, offset, overlay, &state.settings);
+        state.frame = Some(frame);
 
         false.into()
     });
 
     let state_motion = state.clone();
     drawing_area.connect_motion_notify_event(move |widget, event| {
-        let width = widget.allocated_width() as f64;
-        let height = widget.allocated_height() as f64;
-        let center_x = width / 2.0;
-        let center_y = height / 2.0;
-        let radius = if width < height { width } else { height } * 0.35;
-
-        let clicked = get_clicked_button(
-            event.position().0,
-            event.position().1,
-            center_x,
-            center_y,
-            radius,
-            state_motion.borrow().current_menu().len(),
-            -PI / 2.0,
-        );
+        let hovered = state_motion.borrow().button_at(event.position());
 
         let mut state = state_motion.borrow_mut();
-        if clicked != state.hover_button {
-            state.hover_button = clicked;
+        if hovered != state.hover {
+            state.hover = hovered;
             widget.queue_draw();
         }
 
         // Update cursor based on hover state
         if let Some(window) = widget.window() {
-            let cursor = if clicked >= 0 {
+            let cursor = if hovered.is_some() {
                 gdk::Cursor::from_name(widget.display().as_ref(), "hand")
                     .or_else(|| gdk::Cursor::from_name(widget.display().as_ref(), "pointer"))
             } else {
@@ -380,54 +840,33 @@ fn build_ui() {
     let state_click = state.clone();
     let window_clone = window.clone();
     drawing_area.connect_button_press_event(move |widget, event| {
-        let width = widget.allocated_width() as f64;
-        let height = widget.allocated_height() as f64;
-        let center_x = width / 2.0;
-        let center_y = height / 2.0;
-        let radius = if width < height { width } else { height } * 0.35;
-
-        let clicked = get_clicked_button(
-            event.position().0,
-            event.position().1,
-            center_x,
-            center_y,
-            radius,
-            state_click.borrow().current_menu().len(),
-            -PI / 2.0,
-        );
-
-        if clicked >= 0 {
-            let mut state = state_click.borrow_mut();
-            if let Some(button) = state.current_menu().get(clicked as usize).cloned() {
-                if button.has_submenu() {
-                    // Navigate into submenu - don't close window
-                    state.push_submenu(button.children.clone());
-                    drop(state); // Release borrow
-                    widget.queue_draw();
-                    return true.into();
-                } else {
-                    // Execute action and close window
-                    log::info!("Executing action: {}", button.action);
-                    drop(state); // Release borrow before executing command
-                    execute_command(&button.action);
-
-                    // Hide window after executing action
-                    window_clone.hide();
-                    glib::idle_add_once(|| {
-                        gtk::main_quit();
-                    });
-                    return true.into();
-                }
-            }
+        // A double click also delivers a 2BUTTON_PRESS after its second
+        // press, which must not count as another click
+        if event.event_type() != gdk::EventType::ButtonPress {
+            return true.into();
         }
+        let clicked = {
+            let state = state_click.borrow();
+            // The menu changed and has not been drawn since, so nothing on
+            // screen can be hit yet
+            if state.frame.is_none() {
+                return true.into();
+            }
+            state.button_at(event.position())
+        };
 
-        // Hide window on empty area click
-        window_clone.hide();
+        match clicked {
+            Some(button) => activate(&state_click, button, &window_clone, widget),
+            None => {
+                // Hide window on empty area click
+                window_clone.hide();
 
-        // Queue quit to happen immediately after event processing
-        glib::idle_add_once(|| {
-            gtk::main_quit();
-        });
+                // Queue quit to happen immediately after event processing
+                glib::idle_add_once(|| {
+                    gtk::main_quit();
+                });
+            }
+        }
 
         true.into()
     });
@@ -448,24 +887,50 @@ fn build_ui() {
     let window_clone = window.clone();
     let state_key = state.clone();
     let drawing_area_clone_key = drawing_area.clone();
-    window.connect_key_press_event(move |_, key| match key.keyval() {
-        gtk::gdk::keys::constants::Escape => {
-            let mut state = state_key.borrow_mut();
-            if state.in_submenu() {
-                // Go back to parent menu
-                state.pop_submenu();
-                drop(state);
-                drawing_area_clone_key.queue_draw();
-            } else {
-                // Exit application
-                window_clone.hide();
-                glib::idle_add_once(|| {
-                    gtk::main_quit();
-                });
+    window.connect_key_press_event(move |_, key| {
+        use gtk::gdk::keys::constants as keys;
+
+        let nav = match key.keyval() {
+            keys::Up => Some(Nav::Up),
+            keys::Down => Some(Nav::Down),
+            keys::Left => Some(Nav::Left),
+            keys::Right => Some(Nav::Right),
+            keys::Tab => Some(Nav::Next),
+            keys::ISO_Left_Tab => Some(Nav::Previous),
+            _ => None,
+        };
+        if let Some(nav) = nav {
+            state_key.borrow_mut().navigate(nav);
+            drawing_area_clone_key.queue_draw();
+            return true.into();
+        }
+
+        match key.keyval() {
+            keys::Return | keys::KP_Enter | keys::space => {
+                let hover = state_key.borrow().hover;
+                if let Some(button) = hover {
+                    activate(&state_key, button, &window_clone, &drawing_area_clone_key);
+                }
+                true.into()
             }
-            true.into()
+            keys::Escape => {
+                let mut state = state_key.borrow_mut();
+                if state.in_submenu() {
+                    // Go back to parent menu
+                    state.pop_submenu();
+                    drop(state);
+                    drawing_area_clone_key.queue_draw();
+                } else {
+                    // Exit application
+                    window_clone.hide();
+                    glib::idle_add_once(|| {
+                        gtk::main_quit();
+                    });
+                }
+                true.into()
+            }
+            _ => false.into(),
         }
-        _ => false.into(),
     });
 
     window.connect_delete_event(|_, _| {
@@ -476,6 +941,35 @@ fn build_ui() {
     window.show_all();
 }
 
+/// Open the submenu of the button at `level` and `index`, or run its action
+/// and close the menu
+fn activate(
+    state: &RefCell<AppState>,
+    (level, index): (usize, usize),
+    window: &Window,
+    widget: &DrawingArea,
+) {
+    let Some(button) = state.borrow().menu_stack[level].get(index).cloned() else {
+        return;
+    };
+
+    if button.has_submenu() {
+        // Navigate into submenu - don't close window
+        state.borrow_mut().open_submenu(level, index, &button);
+        widget.queue_draw();
+    } else {
+        // Execute action and close window
+        log::info!("Executing action: {}", button.action);
+        execute_command(&button.action);
+
+        // Hide window after executing action
+        window.hide();
+        glib::idle_add_once(|| {
+            gtk::main_quit();
+        });
+    }
+}
+
 fn execute_command(command: &str) {
     if let Err(e) = Command::new("sh").arg("-c").arg(command).spawn() {
         log::error!("Failed to execute command: {} - {}", command, e);
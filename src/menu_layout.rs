@@ -0,0 +1,429 @@
+//! Layout engines.
+//!
+//! A menu level is arranged by one of several layouts: the ring in
+//! `circular_layout`, a grid or a list. Each knows where its buttons are,
+//! so it traces their outlines, draws them and does the keyboard navigation
+//! for its level; the hover animation, the overlay and the icon and label
+//! drawing are shared here.
+//!
+//! Every frame records the layouts it drew along with the hover scales and
+//! slide-in offset it drew them with, and the pointer is tested against
+//! those same outlines, so what is on screen is exactly what can be clicked.
+
+use crate::icons::Icon;
+use crate::settings::Settings;
+use gtk::gdk::cairo::{Context, Format, ImageSurface};
+use gtk::pango;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+
+// Animation configuration constants; the rest come from the layout settings.
+const HOVER_Y_OFFSET: f64 = 8.0;
+// Space kept clear on each side of a label, in pixels.
+pub const LABEL_PADDING: f64 = 4.0;
+
+// Thread-local animation state for smooth transitions, per menu and button.
+thread_local! {
+    static CURRENT_SCALE: RefCell<Vec<Vec<f64>>> = const { RefCell::new(Vec::new()) };
+    static CURRENT_Y_OFFSET: RefCell<Vec<Vec<f64>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A keyboard move between buttons
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Nav {
+    Up,
+    Down,
+    Left,
+    Right,
+    Next,
+    Previous,
+}
+
+/// A button as the layouts draw it: a wedge, a cell or a row
+#[derive(Clone, Debug)]
+pub struct MenuButton {
+    pub label: String,
+    #[allow(dead_code)]
+    pub action: String,
+    pub color: (f64, f64, f64, f64), // RGBA
+    pub hover_color: (f64, f64, f64, f64),
+    pub foreground: (f64, f64, f64, f64), // Icon and label color
+    pub hover_foreground: (f64, f64, f64, f64),
+    pub border_width: f64, // Outline width, 0 for none
+    pub border_color: (f64, f64, f64, f64),
+    pub hover_border_color: (f64, f64, f64, f64),
+    pub image: Option<Icon>,  // Image icon from icon_path or icon_name
+    pub icon: Option<String>, // Icon text (Unicode/Nerd Font symbols, emoji)
+    pub icon_font: String,    // Font family for the icon glyph
+    pub icon_size: f64,       // Icon glyph size in pixels
+    pub label_font: String,   // Font family for the text label
+    pub label_font_size: f64, // Label size in pixels
+    pub show_label: bool,     // Whether to show text label
+}
+
+/// Placement of one menu level's buttons
+pub trait MenuLayout {
+    /// Trace the outline of button `index`, grown by `scale`; drawing and
+    /// hit-testing both use this path
+    fn button_path(&self, cr: &Context, index: usize, scale: f64, settings: &Settings);
+
+    /// Draw the buttons in `paint_order`, each grown by the factor in `scales`
+    fn draw(
+        &self,
+        cr: &Context,
+        buttons: &[MenuButton],
+        hover_button: i32,
+        scales: &[f64],
+        settings: &Settings,
+    );
+
+    /// The button a keyboard move from button `from` of `count` lands on;
+    /// with nothing selected yet, any move selects the first button
+    fn neighbor(&self, from: Option<usize>, nav: Nav, count: usize) -> Option<usize>;
+}
+
+/// A menu level's layout and the buttons drawn in it
+pub struct MenuView<'a> {
+    /// Index of the level in the menu stack
+    pub level: usize,
+    pub layout: Box<dyn MenuLayout>,
+    pub buttons: &'a [MenuButton],
+    /// Opacity the whole level is drawn at while it fades in
+    pub opacity: f64,
+    pub hover_button: i32,
+}
+
+/// Step through `count` buttons in order, wrapping around at either end
+pub fn cycle(from: usize, nav: Nav, count: usize) -> usize {
+    match nav {
+        Nav::Previous | Nav::Up | Nav::Left => (from + count - 1) % count,
+        Nav::Next | Nav::Down | Nav::Right => (from + 1) % count,
+    }
+}
+
+/// Indices of `count` buttons in the order they are painted: the hovered
+/// one last, so that it is on top where it grows over its neighbours
+pub fn paint_order(count: usize, hover_button: i32) -> impl Iterator<Item = usize> {
+    let hover = usize::try_from(hover_button).ok().filter(|&i| i < count);
+    (0..count).filter(move |&i| Some(i) != hover).chain(hover)
+}
+
+/// A menu level as it was drawn in the last frame
+pub struct DrawnLevel {
+    /// Index of the level in the menu stack
+    pub level: usize,
+    pub layout: Box<dyn MenuLayout>,
+    /// Number of buttons drawn
+    pub count: usize,
+    /// Hover scale each button was drawn at
+    pub scales: Vec<f64>,
+    pub hover_button: i32,
+}
+
+/// Everything a frame drew, for the input handlers to test against
+pub struct Frame {
+    /// Translation of the whole menu while it slides in
+    pub offset: (f64, f64),
+    /// The visible levels, innermost first
+    pub levels: Vec<DrawnLevel>,
+}
+
+impl Frame {
+    /// The button drawn at window position (x, y), as its menu level and
+    /// index; where buttons overlap, the one painted on top
+    pub fn button_at(&self, x: f64, y: f64, settings: &Settings) -> Option<(usize, usize)> {
+        let (x, y) = (x - self.offset.0, y - self.offset.1);
+        let surface = ImageSurface::create(Format::A8, 1, 1).ok()?;
+        let cr = Context::new(&surface).ok()?;
+
+        self.levels.iter().rev().find_map(|drawn| {
+            let order: Vec<usize> = paint_order(drawn.count, drawn.hover_button).collect();
+            order
+                .into_iter()
+                .rev()
+                .find(|&index| {
+                    let scale = drawn.scales.get(index).copied().unwrap_or(1.0);
+                    drawn.layout.button_path(&cr, index, scale, settings);
+                    cr.in_fill(x, y).unwrap_or(false)
+                })
+                .map(|index| (drawn.level, index))
+        })
+    }
+
+    /// The button a keyboard move from `from` in menu `level` lands on
+    pub fn neighbor(&self, level: usize, from: Option<usize>, nav: Nav) -> Option<usize> {
+        let drawn = self.levels.iter().find(|drawn| drawn.level == level)?;
+        drawn.layout.neighbor(from, nav, drawn.count)
+    }
+}
+
+/// Trace a rectangle with corners rounded by up to `radius`
+pub fn rounded_rect_path(cr: &Context, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+    let radius = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+    cr.new_path();
+    cr.arc(x + width - radius, y + radius, radius, -PI / 2.0, 0.0);
+    cr.arc(
+        x + width - radius,
+        y + height - radius,
+        radius,
+        0.0,
+        PI / 2.0,
+    );
+    cr.arc(x + radius, y + height - radius, radius, PI / 2.0, PI);
+    cr.arc(x + radius, y + radius, radius, PI, 1.5 * PI);
+    cr.close_path();
+}
+
+/// Fill the current path in the button's color and outline it if it has a
+/// border, returning the color for its icon and label
+pub fn fill_button(cr: &Context, button: &MenuButton, is_hover: bool) -> (f64, f64, f64, f64) {
+    let (color, foreground, border) = if is_hover {
+        (
+            button.hover_color,
+            button.hover_foreground,
+            button.hover_border_color,
+        )
+    } else {
+        (button.color, button.foreground, button.border_color)
+    };
+    cr.set_source_rgba(color.0, color.1, color.2, color.3);
+
+    // Outline the button only if it has a border
+    if button.border_width > 0.0 {
+        let _ = cr.fill_preserve();
+        cr.set_line_width(button.border_width);
+        cr.set_source_rgba(border.0, border.1, border.2, border.3);
+        let _ = cr.stroke();
+    } else {
+        let _ = cr.fill();
+    }
+    foreground
+}
+
+/// Draw the button's icon centered on (x, y); an image icon takes
+/// precedence over the glyph
+pub fn draw_icon(
+    cr: &Context,
+    button: &MenuButton,
+    x: f64,
+    y: f64,
+    size: f64,
+    foreground: (f64, f64, f64, f64),
+) {
+    match &button.image {
+        Some(image) => draw_image_icon(cr, image, x, y, size, foreground.3),
+        None => draw_glyph_icon(cr, button, x, y, size, foreground),
+    }
+}
+
+/// Draw the button's label on one line `width` wide from `x`, with its
+/// baseline at `baseline`, ellipsizing anything longer
+#[allow(clippy::too_many_arguments)]
+pub fn draw_label(
+    cr: &Context,
+    button: &MenuButton,
+    x: f64,
+    baseline: f64,
+    width: f64,
+    alignment: pango::Alignment,
+    foreground: (f64, f64, f64, f64),
+    settings: &Settings,
+) {
+    let layout = text_layout(
+        cr,
+        &button.label,
+        &button.label_font,
+        button.label_font_size,
+    );
+    layout.set_width((width * pango::SCALE as f64) as i32);
+    layout.set_ellipsize(pango::EllipsizeMode::End);
+    layout.set_alignment(alignment);
+    layout.set_single_paragraph_mode(true);
+
+    cr.move_to(x, baseline - layout.baseline() as f64 / pango::SCALE as f64);
+    cr.set_source_rgba(
+        foreground.0,
+        foreground.1,
+        foreground.2,
+        foreground.3 * settings.label_alpha,
+    );
+    pangocairo::functions::show_layout(cr, &layout);
+}
+
+/// Draw an image icon centered on (x, y), fitted into a `size`x`size` square
+fn draw_image_icon(cr: &Context, image: &Icon, x: f64, y: f64, size: f64, alpha: f64) {
+    let factor = size / image.width.max(image.height);
+    let (left, top) = snap_to_device(
+        cr,
+        x - image.width * factor / 2.0,
+        y - image.height * factor / 2.0,
+    );
+    let _ = cr.save();
+    cr.translate(left, top);
+    cr.scale(factor, factor);
+    if cr.set_source_surface(&image.surface, 0.0, 0.0).is_ok() {
+        let _ = cr.paint_with_alpha(alpha);
+    }
+    let _ = cr.restore();
+}
+
+/// Move (x, y) to the nearest corner of a device pixel, so that an image
+/// drawn from there at its own resolution is not resampled and stays sharp
+/// at any scale factor
+fn snap_to_device(cr: &Context, x: f64, y: f64) -> (f64, f64) {
+    let (device_x, device_y) = cr.user_to_device(x, y);
+    cr.device_to_user(device_x.round(), device_y.round())
+        .unwrap_or((x, y))
+}
+
+/// Draw the button's icon glyph centered on (x, y)
+fn draw_glyph_icon(
+    cr: &Context,
+    button: &MenuButton,
+    x: f64,
+    y: f64,
+    size: f64,
+    foreground: (f64, f64, f64, f64),
+) {
+    // Use custom icon if provided, otherwise use a generic default
+    let symbol = button.icon.as_deref().unwrap_or_else(|| {
+        // If no custom icon is provided, use a generic bullet point
+        // Users should specify an icon in their config for any button
+        log::debug!(
+            "No icon specified for label: '{}', using default bullet",
+            button.label
+        );
+        "•"
+    });
+
+    // Draw the icon with the configured (Nerd) font, centering its ink
+    // rather than its line box so symbols and emoji sit in the middle
+    let layout = text_layout(cr, symbol, &button.icon_font, size);
+    let (ink, _) = layout.pixel_extents();
+    cr.move_to(
+        x - ink.x() as f64 - ink.width() as f64 / 2.0,
+        y - ink.y() as f64 - ink.height() as f64 / 2.0,
+    );
+    cr.set_source_rgba(foreground.0, foreground.1, foreground.2, foreground.3);
+    pangocairo::functions::show_layout(cr, &layout);
+}
+
+/// Lay out `text` in `family` at `size` pixels.
+///
+/// `family` may be a comma-separated list; glyphs missing from all of them
+/// fall back through fontconfig, so emoji and other scripts still render.
+fn text_layout(cr: &Context, text: &str, family: &str, size: f64) -> pango::Layout {
+    let layout = pangocairo::functions::create_layout(cr);
+    let mut font = pango::FontDescription::new();
+    font.set_family(family);
+    font.set_absolute_size(size * pango::SCALE as f64);
+    layout.set_font_description(Some(&font));
+    layout.set_text(text);
+    layout
+}
+
+/// Draw the overlay and every visible menu level, the innermost first,
+/// shifted by `offset`, and return where everything was drawn
+pub fn draw_menu(
+    cr: &Context,
+    views: Vec<MenuView>,
+    offset: (f64, f64),
+    overlay: (f64, f64, f64, f64),
+    settings: &Settings,
+) -> Frame {
+    // Draw semi-opaque overlay for frosted/blur effect
+    cr.set_source_rgba(overlay.0, overlay.1, overlay.2, overlay.3);
+    let _ = cr.paint();
+
+    // Update animation states smoothly using thread-local storage.
+    CURRENT_SCALE.with(|scale_cell| {
+        CURRENT_Y_OFFSET.with(|offset_cell| {
+            let mut scales = scale_cell.borrow_mut();
+            let mut offsets = offset_cell.borrow_mut();
+
+            // Ensure vectors are properly sized.
+            if scales.len() < views.len() {
+                scales.resize(views.len(), Vec::new());
+            }
+            if offsets.len() < views.len() {
+                offsets.resize(views.len(), Vec::new());
+            }
+
+            for (view, (scales, offsets)) in
+                views.iter().zip(scales.iter_mut().zip(offsets.iter_mut()))
+            {
+                let (buttons, hover_button) = (&view.buttons, view.hover_button);
+                if scales.len() < buttons.len() {
+                    scales.resize(buttons.len(), 1.0);
+                }
+                if offsets.len() < buttons.len() {
+                    offsets.resize(buttons.len(), 0.0);
+                }
+
+                for (i, _button) in buttons.iter().enumerate() {
+                    let target_scale = if i as i32 == hover_button {
+                        settings.hover_scale
+                    } else {
+                        1.0
+                    };
+
+                    if scales[i] < target_scale {
+                        scales[i] += settings.animation_speed;
+                        if scales[i] > target_scale {
+                            scales[i] = target_scale;
+                        }
+                    } else if scales[i] > target_scale {
+                        scales[i] -= settings.animation_speed;
+                        if scales[i] < target_scale {
+                            scales[i] = target_scale;
+                        }
+                    }
+
+                    // Animate radial expansion
+                    let target_y = if i as i32 == hover_button {
+                        HOVER_Y_OFFSET
+                    } else {
+                        0.0
+                    };
+                    if (offsets[i] - target_y).abs() > 0.1 {
+                        offsets[i] += (target_y - offsets[i]) * 0.05;
+                    } else {
+                        offsets[i] = target_y;
+                    }
+                }
+            }
+        })
+    });
+
+    let _ = cr.save();
+    cr.translate(offset.0, offset.1);
+    let levels = views
+        .into_iter()
+        .enumerate()
+        .map(|(v, view)| {
+            let mut scales = CURRENT_SCALE.with(|cell| cell.borrow()[v].clone());
+            scales.truncate(view.buttons.len());
+            // A fading level is drawn opaque and then blended in as a whole,
+            // so overlapping parts of it do not show through each other
+            if view.opacity < 1.0 {
+                cr.push_group();
+            }
+            view.layout
+                .draw(cr, view.buttons, view.hover_button, &scales, settings);
+            if view.opacity < 1.0 {
+                let _ = cr.pop_group_to_source();
+                let _ = cr.paint_with_alpha(view.opacity);
+            }
+            DrawnLevel {
+                level: view.level,
+                layout: view.layout,
+                count: view.buttons.len(),
+                scales,
+                hover_button: view.hover_button,
+            }
+        })
+        .collect();
+    let _ = cr.restore();
+
+    Frame { offset, levels }
+}
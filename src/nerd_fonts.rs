@@ -0,0 +1,86 @@
+//! Nerd Font icon names.
+//!
+//! Buttons may name their icon the way the Nerd Fonts cheat sheet does,
+//! e.g. `"nf-fa-lock"` or just `"md-power"`, instead of pasting the glyph.
+//! Names are looked up in a table bundled from Nerd Fonts' glyph list.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+const TABLE: &str = include_str!("../data/nerd-font-names.tsv");
+
+struct Names {
+    glyphs: HashMap<&'static str, char>,
+    /// Icon set prefixes such as "fa" and "md"
+    classes: HashSet<&'static str>,
+}
+
+fn names() -> &'static Names {
+    static NAMES: OnceLock<Names> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        let glyphs: HashMap<&str, char> = TABLE
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let (name, code) = line.split_once('\t')?;
+                let glyph = char::from_u32(u32::from_str_radix(code, 16).ok()?)?;
+                Some((name, glyph))
+            })
+            .collect();
+        let classes = glyphs
+            .keys()
+            .filter_map(|name| name.split_once('-').map(|(class, _)| class))
+            .collect();
+        Names { glyphs, classes }
+    })
+}
+
+/// Whether `icon` is written as a Nerd Font name rather than literal text.
+///
+/// That is anything starting with `nf-`, or a known icon set prefix
+/// followed by a lowercase name, such as `md-power`.
+pub fn is_icon_name(icon: &str) -> bool {
+    if icon.starts_with("nf-") {
+        return true;
+    }
+    match icon.split_once('-') {
+        Some((class, name)) => {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-".contains(c))
+                && names().classes.contains(class)
+        }
+        None => false,
+    }
+}
+
+/// Look up the glyph for a Nerd Font name, with or without the `nf-` prefix
+pub fn lookup(name: &str) -> Option<char> {
+    let name = name.strip_prefix("nf-").unwrap_or(name);
+    names().glyphs.get(name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("nf-fa-lock"), Some('\u{f023}'));
+        assert_eq!(lookup("fa-lock"), Some('\u{f023}'));
+        assert_eq!(lookup("md-power"), Some('\u{f0425}'));
+        assert_eq!(lookup("nf-fa-lokc"), None);
+    }
+
+    #[test]
+    fn test_is_icon_name() {
+        assert!(is_icon_name("nf-fa-lock"));
+        assert!(is_icon_name("md-power"));
+        assert!(is_icon_name("nf-bogus"));
+        assert!(!is_icon_name("\u{f023}"));
+        assert!(!is_icon_name("🔒"));
+        assert!(!is_icon_name("x-y"));
+        assert!(!is_icon_name("md-"));
+    }
+}
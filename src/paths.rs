@@ -0,0 +1,197 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Application directory name under each configuration root
+const APP_DIR: &str = "snmenu";
+/// Directory name used before the project was renamed (deprecated)
+const LEGACY_APP_DIR: &str = "cpmenu";
+
+/// The environment variables the configuration roots are built from
+struct Env {
+    config_home: Option<OsString>,
+    home: Option<OsString>,
+    config_dirs: Option<String>,
+}
+
+impl Env {
+    fn current() -> Self {
+        Self {
+            config_home: env::var_os("XDG_CONFIG_HOME"),
+            home: env::var_os("HOME"),
+            config_dirs: env::var("XDG_CONFIG_DIRS").ok(),
+        }
+    }
+}
+
+/// Configuration roots in priority order, highest first.
+///
+/// `user` selects whether the per-user root ($XDG_CONFIG_HOME, or ~/.config)
+/// is included; the system roots ($XDG_CONFIG_DIRS, /etc, /usr/local/etc)
+/// always are.
+fn config_roots(env: &Env, user: bool) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    if user {
+        match env.config_home.as_ref().filter(|dir| !dir.is_empty()) {
+            Some(dir) => roots.push(PathBuf::from(dir)),
+            None => {
+                if let Some(home) = &env.home {
+                    roots.push(Path::new(home).join(".config"));
+                }
+            }
+        }
+    }
+
+    let config_dirs = (env.config_dirs.as_deref())
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or("/etc/xdg");
+    roots.extend(
+        config_dirs
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from),
+    );
+
+    roots.push(PathBuf::from("/etc"));
+    roots.push(PathBuf::from("/usr/local/etc"));
+    roots.dedup();
+    roots
+}
+
+/// Directories searched for snmenu configuration files, highest priority first
+pub fn config_dirs(user: bool) -> Vec<PathBuf> {
+    config_roots(&Env::current(), user)
+        .into_iter()
+        .map(|root| root.join(APP_DIR))
+        .collect()
+}
+
+/// Find the first existing file among `dirs`, trying each name in order
+fn find_in(dirs: &[PathBuf], names: &[&str]) -> Option<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+/// Find a configuration file in the snmenu directories, falling back to the
+/// deprecated cpmenu directories.
+pub fn find_config_file(names: &[&str]) -> Option<PathBuf> {
+    find_config_file_in(true, names)
+}
+
+/// Like [`find_config_file`], but only searches the system-wide directories
+pub fn find_system_config_file(names: &[&str]) -> Option<PathBuf> {
+    find_config_file_in(false, names)
+}
+
+fn find_config_file_in(user: bool, names: &[&str]) -> Option<PathBuf> {
+    let (path, legacy) = search(&Env::current(), user, names)?;
+    if legacy {
+        log::warn!(
+            "Using {} from the deprecated '{}' directory; move it to '{}'",
+            path.display(),
+            LEGACY_APP_DIR,
+            APP_DIR
+        );
+    }
+    Some(path)
+}
+
+/// Find the first of `names` in the snmenu directories, then in the cpmenu
+/// ones, and whether it was found in the latter
+fn search(env: &Env, user: bool, names: &[&str]) -> Option<(PathBuf, bool)> {
+    let roots = config_roots(env, user);
+    let find_under = |app_dir: &str| {
+        let dirs: Vec<PathBuf> = roots.iter().map(|root| root.join(app_dir)).collect();
+        find_in(&dirs, names)
+    };
+    match find_under(APP_DIR) {
+        Some(path) => Some((path, false)),
+        None => find_under(LEGACY_APP_DIR).map(|path| (path, true)),
+    }
+}
+
+/// pywal's generated `colors.json`, if pywal has been run
+pub fn wal_colors_file() -> Option<PathBuf> {
+    let cache = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(cache.join("wal").join("colors.json")).filter(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn env(config_home: Option<&str>, config_dirs: Option<&str>) -> Env {
+        Env {
+            config_home: config_home.map(OsString::from),
+            home: Some(OsString::from("/home/user")),
+            config_dirs: config_dirs.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_config_roots() {
+        let roots = config_roots(&env(Some("/cfg"), Some("/opt/xdg:/etc/xdg")), true);
+        assert_eq!(
+            roots,
+            ["/cfg", "/opt/xdg", "/etc/xdg", "/etc", "/usr/local/etc"].map(PathBuf::from)
+        );
+
+        // An empty or unset variable falls back to its default
+        let roots = config_roots(&env(Some(""), None), true);
+        assert_eq!(
+            roots,
+            ["/home/user/.config", "/etc/xdg", "/etc", "/usr/local/etc"].map(PathBuf::from)
+        );
+
+        let roots = config_roots(&env(Some("/cfg"), Some("")), false);
+        assert_eq!(
+            roots,
+            ["/etc/xdg", "/etc", "/usr/local/etc"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let dir = std::env::temp_dir().join(format!("snmenu-paths-{}", std::process::id()));
+        let user = dir.join("user");
+        let system = dir.join("system");
+        let env = env(user.to_str(), system.to_str());
+        let write = |path: PathBuf| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "[]").unwrap();
+            path
+        };
+
+        let user_layout = write(user.join("snmenu/layout.json"));
+        let system_layout = write(system.join("snmenu/layout"));
+        let names = ["layout", "layout.json"];
+        assert_eq!(
+            search(&env, true, &names),
+            Some((user_layout.clone(), false))
+        );
+        // The system-wide search skips the user directory
+        assert_eq!(
+            search(&env, false, &names),
+            Some((system_layout.clone(), false))
+        );
+
+        // Any snmenu directory comes before the deprecated cpmenu ones
+        let legacy_layout = write(user.join("cpmenu/layout"));
+        fs::remove_file(&user_layout).unwrap();
+        assert_eq!(
+            search(&env, true, &names),
+            Some((system_layout.clone(), false))
+        );
+
+        fs::remove_file(&system_layout).unwrap();
+        assert_eq!(search(&env, true, &names), Some((legacy_layout, true)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,605 @@
+//! Layout includes and named menu references.
+//!
+//! A layout may `include` other layout files to import their named `menus`,
+//! and any button may point at a named menu with `"submenu": "@name"`
+//! instead of spelling out its `children`. [`resolve_layout`] loads the
+//! includes and expands every reference into plain `children`, so the rest
+//! of the program only ever sees an ordinary button tree, with theme colors
+//! and Nerd Font icon names already applied. Style defaults are left in
+//! place for [`style_layout`](crate::style::style_layout), so `--check` can
+//! lint them where they are written. Resolving carries on past names it
+//! cannot look up and includes it cannot load, so `--check` can report
+//! them all at once.
+
+use crate::config::{menu_path, Button, Layout, Source};
+use crate::error::{ConfigError, Location};
+use crate::format::{join, LayoutFormat, Step};
+use crate::nerd_fonts::{self, is_icon_name};
+use crate::theme::Palette;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Parser used for included files (validating or not)
+pub type ParseFn = fn(&str, &str, LayoutFormat) -> Result<Layout, ConfigError>;
+
+/// Named menus with the file each one was defined in
+type Menus = BTreeMap<String, (Vec<Button>, Rc<Source>)>;
+
+/// Load includes and expand named menu references.
+///
+/// `source` is the text `layout` was parsed from; includes are resolved
+/// relative to the directory of its file (or the working directory for
+/// stdin). `"@name"` colors are looked up in `palette` and icon names such
+/// as `"nf-fa-lock"` in the bundled Nerd Font table.
+///
+/// Every error found is returned along with the layout, resolved as far as
+/// possible: unknown names and broken references are left as written, and
+/// includes that cannot be loaded are skipped.
+pub fn resolve_layout(
+    layout: Layout,
+    source: &Source,
+    parse: ParseFn,
+    palette: Option<&Palette>,
+) -> (Layout, Vec<ConfigError>) {
+    let mut layout = layout;
+    let mut errors = Vec::new();
+    let root = Rc::new(source.clone());
+    let mut menus = Menus::new();
+    let mut include_stack: Vec<PathBuf> = (root.path.as_deref())
+        .and_then(|path| path.canonicalize().ok())
+        .into_iter()
+        .collect();
+
+    collect_includes(
+        &layout.include,
+        &root,
+        parse,
+        &mut menus,
+        &mut include_stack,
+        &mut errors,
+    );
+    // A file's own menus override the ones it includes
+    for (name, buttons) in std::mem::take(&mut layout.menus) {
+        menus.insert(name, (buttons, root.clone()));
+    }
+
+    // Theme colors and icon names are looked up first so errors point into
+    // the file that uses them
+    let mut defaults = [
+        ("color", &mut layout.defaults.color),
+        ("hover_color", &mut layout.defaults.hover_color),
+        ("foreground", &mut layout.defaults.foreground),
+    ];
+    let defaults_at = [Step::key("defaults")];
+    apply_palette(
+        &mut defaults,
+        "defaults",
+        &root,
+        palette,
+        &defaults_at,
+        &mut errors,
+    );
+    apply_names(
+        &mut layout.buttons,
+        "",
+        &root,
+        palette,
+        &[Step::Buttons],
+        &mut errors,
+    );
+    for (name, (buttons, source)) in menus.iter_mut() {
+        let path = format!("@{}", name);
+        apply_names(buttons, &path, source, palette, &menu_at(name), &mut errors);
+    }
+
+    let buttons = expand(
+        &layout.buttons,
+        "",
+        &root,
+        &[Step::Buttons],
+        &menus,
+        &mut Vec::new(),
+        &mut errors,
+    );
+
+    // A named menu is expanded at each use, so a broken reference in it
+    // fails once per use; report it once, where it is written
+    let mut seen = HashSet::new();
+    errors.retain(|error| match error {
+        ConfigError::Schema { location, .. }
+        | ConfigError::UnknownMenu { location, .. }
+        | ConfigError::MenuCycle { location, .. } => {
+            seen.insert((std::mem::discriminant(error), location.to_string()))
+        }
+        _ => true,
+    });
+
+    let layout = Layout {
+        settings: layout.settings,
+        settings_keys: layout.settings_keys,
+        defaults: layout.defaults,
+        buttons,
+        ..Layout::default()
+    };
+    (layout, errors)
+}
+
+/// Where the named menu `name` is written in its file
+fn menu_at(name: &str) -> [Step; 2] {
+    [Step::key("menus"), Step::key(name)]
+}
+
+/// Replace `"@name"` colors below `buttons` with their palette values and
+/// Nerd Font icon names with their glyphs. `at` is where `buttons` are
+/// written in `source`.
+fn apply_names(
+    buttons: &mut [Button],
+    parent_path: &str,
+    source: &Source,
+    palette: Option<&Palette>,
+    at: &[Step],
+    errors: &mut Vec<ConfigError>,
+) {
+    for (index, button) in buttons.iter_mut().enumerate() {
+        let path = menu_path(parent_path, &button.label);
+        let at = join(at, Step::Index(index));
+
+        if let Some(icon) = button.icon.as_mut().filter(|icon| is_icon_name(icon)) {
+            match nerd_fonts::lookup(icon) {
+                Some(glyph) => *icon = glyph.to_string(),
+                None => {
+                    // The older key name is still accepted
+                    let location = ["icon", "icon_char"]
+                        .into_iter()
+                        .map(|key| source.locate(&join(&at, Step::key(key))))
+                        .find(|location| location.position.is_some())
+                        .unwrap_or_else(|| Location::new(&source.origin));
+                    errors.push(ConfigError::UnknownIcon {
+                        location,
+                        menu_path: path.clone(),
+                        name: icon.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut colors = [
+            ("color", &mut button.color),
+            ("hover_color", &mut button.hover_color),
+            ("foreground", &mut button.foreground),
+        ];
+        apply_palette(&mut colors, &path, source, palette, &at, errors);
+        if let Some(defaults) = &mut button.defaults {
+            let mut colors = [
+                ("color", &mut defaults.color),
+                ("hover_color", &mut defaults.hover_color),
+                ("foreground", &mut defaults.foreground),
+            ];
+            let defaults_path = format!("{} defaults", path);
+            let defaults_at = join(&at, Step::key("defaults"));
+            apply_palette(
+                &mut colors,
+                &defaults_path,
+                source,
+                palette,
+                &defaults_at,
+                errors,
+            );
+        }
+        let children_at = join(&at, Step::key("children"));
+        apply_names(
+            &mut button.children,
+            &path,
+            source,
+            palette,
+            &children_at,
+            errors,
+        );
+    }
+}
+
+/// Replace `"@name"` values among `colors`, each with its key in the file.
+///
+/// A name missing from the palette is cleared, so it is not reported again
+/// as an invalid color.
+fn apply_palette(
+    colors: &mut [(&str, &mut Option<String>)],
+    menu_path: &str,
+    source: &Source,
+    palette: Option<&Palette>,
+    at: &[Step],
+    errors: &mut Vec<ConfigError>,
+) {
+    for (key, color) in colors.iter_mut() {
+        let Some(name) = color.as_deref().and_then(|c| c.strip_prefix('@')) else {
+            continue;
+        };
+        let value = palette.and_then(|palette| palette.get(name));
+        match value {
+            Some(value) => **color = Some(value.to_string()),
+            None => {
+                errors.push(ConfigError::UnknownColor {
+                    location: source.locate(&join(at, Step::key(key))),
+                    menu_path: menu_path.to_string(),
+                    name: name.to_string(),
+                    theme: palette.map_or("no theme selected".to_string(), |palette| {
+                        format!("not in theme {}", palette.origin)
+                    }),
+                });
+                **color = None;
+            }
+        }
+    }
+}
+
+/// Load each file included by `source` (and its own includes) and import
+/// its named menus
+fn collect_includes(
+    includes: &[String],
+    source: &Rc<Source>,
+    parse: ParseFn,
+    menus: &mut Menus,
+    stack: &mut Vec<PathBuf>,
+    errors: &mut Vec<ConfigError>,
+) {
+    let dir = (source.path.as_deref())
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(""));
+    for (index, include) in includes.iter().enumerate() {
+        let location = || source.locate(&[Step::key("include"), Step::Index(index)]);
+        let path = dir.join(include);
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(e) => {
+                errors.push(ConfigError::Schema {
+                    location: location(),
+                    message: format!("cannot include {}: {}", path.display(), e),
+                });
+                continue;
+            }
+        };
+
+        if stack.contains(&canonical) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|path| path.display().to_string())
+                .collect();
+            errors.push(ConfigError::IncludeCycle {
+                location: location(),
+                chain: chain.join(" -> "),
+            });
+            continue;
+        }
+
+        let loaded = Source::from_file(&path).and_then(|source| {
+            let included = parse(&source.content, &source.origin, source.format)?;
+            Ok((Rc::new(source), included))
+        });
+        let (included_source, included) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        let origin = &included_source.origin;
+        if !included.buttons.is_empty() {
+            log::warn!(
+                "{}: buttons in included layouts are ignored; only named menus are imported",
+                origin
+            );
+        }
+
+        stack.push(canonical);
+        collect_includes(
+            &included.include,
+            &included_source,
+            parse,
+            menus,
+            stack,
+            errors,
+        );
+        stack.pop();
+
+        for (name, buttons) in included.menus {
+            menus.insert(name, (buttons, included_source.clone()));
+        }
+    }
+}
+
+/// Replace every `submenu` reference below `buttons` with the referenced menu.
+///
+/// `at` is where `buttons` are written in `source`. `expanding` holds the
+/// chain of menu names currently being expanded, so a reference back into
+/// it is a cycle rather than infinite recursion. A reference that cannot be
+/// expanded is kept as written.
+fn expand(
+    buttons: &[Button],
+    parent_path: &str,
+    source: &Rc<Source>,
+    at: &[Step],
+    menus: &Menus,
+    expanding: &mut Vec<String>,
+    errors: &mut Vec<ConfigError>,
+) -> Vec<Button> {
+    let mut expanded = Vec::with_capacity(buttons.len());
+
+    for (index, button) in buttons.iter().enumerate() {
+        let mut button = button.clone();
+        let path = menu_path(parent_path, &button.label);
+        let at = join(at, Step::Index(index));
+
+        match button.submenu.take() {
+            Some(reference) => {
+                let name = reference.trim_start_matches('@');
+                let location = || source.locate(&join(&at, Step::key("submenu")));
+
+                let menu = if !button.children.is_empty() {
+                    Err(ConfigError::Schema {
+                        location: location(),
+                        message: format!("{} has both \"submenu\" and \"children\"", path),
+                    })
+                } else if expanding.iter().any(|n| n == name) {
+                    let chain: Vec<String> = expanding
+                        .iter()
+                        .map(String::as_str)
+                        .chain(std::iter::once(name))
+                        .map(|n| format!("@{}", n))
+                        .collect();
+                    Err(ConfigError::MenuCycle {
+                        location: location(),
+                        chain: chain.join(" -> "),
+                    })
+                } else {
+                    menus.get(name).ok_or_else(|| ConfigError::UnknownMenu {
+                        location: location(),
+                        menu_path: path.clone(),
+                        name: name.to_string(),
+                    })
+                };
+
+                match menu {
+                    Ok((menu, menu_source)) => {
+                        // The menu's buttons are written where it is defined
+                        expanding.push(name.to_string());
+                        button.children = expand(
+                            menu,
+                            &path,
+                            menu_source,
+                            &menu_at(name),
+                            menus,
+                            expanding,
+                            errors,
+                        );
+                        button.expanded_from = Some(name.to_string());
+                        expanding.pop();
+                    }
+                    Err(e) => {
+                        errors.push(e);
+                        button.submenu = Some(reference);
+                    }
+                }
+            }
+            None => {
+                let children_at = join(&at, Step::key("children"));
+                button.children = expand(
+                    &button.children,
+                    &path,
+                    source,
+                    &children_at,
+                    menus,
+                    expanding,
+                    errors,
+                );
+            }
+        }
+
+        expanded.push(button);
+    }
+
+    expanded
+}
+
+/// Parse and resolve `content` as a JSON layout read from no file and
+/// with no theme, failing with the first error found
+#[cfg(test)]
+pub fn resolve_json(content: &str, parse: ParseFn) -> Result<Layout, ConfigError> {
+    let source = Source::new(content, "layout", LayoutFormat::Json);
+    let layout = parse(content, &source.origin, source.format)?;
+    match resolve_layout(layout, &source, parse, None) {
+        (layout, errors) if errors.is_empty() => Ok(layout),
+        (_, errors) => Err(errors.into_iter().next().unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+
+    fn resolve(content: &str) -> Result<Layout, ConfigError> {
+        resolve_json(content, parse_config)
+    }
+
+    #[test]
+    fn test_expand_named_menus() {
+        let layout = resolve(
+            r#"{
+                "menus": {
+                    "power": [
+                        {"label": "reboot", "action": "systemctl reboot"},
+                        {"label": "more", "submenu": "@more"}
+                    ],
+                    "more": [{"label": "hibernate", "action": "systemctl hibernate"}]
+                },
+                "buttons": [{"label": "power", "submenu": "@power"}]
+            }"#,
+        )
+        .unwrap();
+
+        let power = &layout.buttons[0];
+        assert_eq!(power.submenu, None);
+        assert_eq!(power.children[0].label, "reboot");
+        assert_eq!(power.children[1].children[0].label, "hibernate");
+        assert!(layout.menus.is_empty());
+    }
+
+    #[test]
+    fn test_menu_errors() {
+        let err = resolve(
+            r#"{
+                "menus": {
+                    "a": [{"label": "to-b", "submenu": "@b"}],
+                    "b": [{"label": "to-a", "submenu": "@a"}]
+                },
+                "buttons": [{"label": "start", "submenu": "@a"}]
+            }"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::MenuCycle { .. }));
+        assert!(err
+            .to_string()
+            .ends_with("menu reference cycle: @a -> @b -> @a"));
+
+        let err = resolve(r#"[{"label": "power", "submenu": "@missing"}]"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:41: unknown menu '@missing' in power"
+        );
+
+        // The reference is located at the button that makes it, not at an
+        // earlier occurrence of the same text
+        let err = resolve(
+            "[{\"label\": \"docs\", \"text\": \"@missing\"},\n {\"label\": \"power\", \"submenu\": \"@missing\"}]",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:41: unknown menu '@missing' in power"
+        );
+    }
+
+    #[test]
+    fn test_theme_colors() {
+        let content = r#"{
+            "defaults": {"hover_color": "@blue"},
+            "buttons": [{"label": "lock", "action": "hyprlock", "color": "@red"}]
+        }"#;
+        let palette = Palette::parse(
+            r##"{"red": "#BF616A", "blue": "#5E81AC"}"##,
+            "nord.json",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        let source = Source::new(content, "layout", LayoutFormat::Json);
+        let layout = parse_config(content, "layout", LayoutFormat::Json).unwrap();
+        let (layout, errors) = resolve_layout(layout, &source, parse_config, Some(&palette));
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(layout.buttons[0].color.as_deref(), Some("#BF616A"));
+        assert_eq!(layout.defaults.hover_color.as_deref(), Some("#5E81AC"));
+
+        let err = resolve(content).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:47: unknown color '@blue' in defaults (no theme selected)"
+        );
+    }
+
+    #[test]
+    fn test_icon_names() {
+        let layout = resolve(
+            r#"{
+                "menus": {"power": [{"label": "off", "action": "poweroff", "icon": "md-power"}]},
+                "buttons": [
+                    {"label": "lock", "action": "hyprlock", "icon": "nf-fa-lock"},
+                    {"label": "power", "icon": "🔌", "submenu": "@power"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(layout.buttons[0].icon.as_deref(), Some("\u{f023}"));
+        assert_eq!(layout.buttons[1].icon.as_deref(), Some("🔌"));
+        assert_eq!(
+            layout.buttons[1].children[0].icon.as_deref(),
+            Some("\u{f0425}")
+        );
+
+        let err = resolve(r#"[{"label": "lock", "action": "hyprlock", "icon": "nf-fa-lokc"}]"#)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:61: unknown Nerd Font icon 'nf-fa-lokc' in lock"
+        );
+    }
+
+    #[test]
+    fn test_resolve_all_errors() {
+        let content = r#"{
+            "include": ["/nonexistent/snmenu/power.json"],
+            "menus": {"tools": [{"label": "gone", "submenu": "@missing"}]},
+            "buttons": [
+                {"label": "lock", "action": "hyprlock", "icon": "nf-fa-lokc", "color": "@red"},
+                {"label": "tools", "submenu": "@tools"},
+                {"label": "more", "submenu": "@tools"}
+            ]
+        }"#;
+        let source = Source::new(content, "layout", LayoutFormat::Json);
+        let layout = parse_config(content, "layout", LayoutFormat::Json).unwrap();
+        let (layout, errors) = resolve_layout(layout, &source, parse_config, None);
+
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors[0].starts_with("layout:2:56: cannot include /nonexistent/snmenu/power.json"));
+        assert_eq!(
+            errors[1..],
+            [
+                "layout:5:76: unknown Nerd Font icon 'nf-fa-lokc' in lock",
+                "layout:5:93: unknown color '@red' in lock (no theme selected)",
+                // Reported once, though the menu is used twice
+                "layout:3:71: unknown menu '@missing' in tools > gone",
+            ]
+        );
+
+        // What cannot be resolved is left for the linter, without also
+        // reading as a button with no submenu or a bad color
+        assert_eq!(layout.buttons[0].color, None);
+        let gone = &layout.buttons[1].children[0];
+        assert_eq!(gone.submenu.as_deref(), Some("@missing"));
+        assert!(gone.has_submenu());
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = std::env::temp_dir().join(format!("snmenu-include-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("power.json"),
+            r#"{"include": ["main.json"], "menus": {"power": [{"label": "off", "action": "poweroff"}]}}"#,
+        )
+        .unwrap();
+        let main =
+            r#"{"include": ["power.json"], "buttons": [{"label": "power", "submenu": "@power"}]}"#;
+        std::fs::write(dir.join("main.json"), main).unwrap();
+
+        let load = || {
+            let source = Source::from_file(&dir.join("main.json")).unwrap();
+            let layout = parse_config(&source.content, &source.origin, source.format).unwrap();
+            resolve_layout(layout, &source, parse_config, None)
+        };
+        let (_, errors) = load();
+        assert!(matches!(errors[..], [ConfigError::IncludeCycle { .. }]));
+
+        std::fs::write(
+            dir.join("power.json"),
+            r#"{"menus": {"power": [{"label": "off", "action": "poweroff"}]}}"#,
+        )
+        .unwrap();
+        let (layout, errors) = load();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(layout.buttons[0].children[0].label, "off");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
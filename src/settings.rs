@@ -0,0 +1,736 @@
+use crate::color::{parse_color, Color};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Invoke `$handle!` with the name of every setting.
+///
+/// Code that goes through the settings one by one uses this and destructures
+/// `Settings` in full, so a new setting that it does not handle fails to
+/// compile rather than being silently skipped.
+macro_rules! each_setting {
+    ($handle:ident) => {
+        $handle!(
+            layout,
+            radius_ratio,
+            radius,
+            min_radius,
+            max_radius,
+            inner_radius_ratio,
+            start_angle,
+            sweep_angle,
+            direction,
+            anchor,
+            submenu_mode,
+            grid_columns,
+            cell_size,
+            cell_spacing,
+            list_width,
+            row_height,
+            icon_size,
+            icon_font,
+            label_font,
+            label_font_size,
+            label_offset,
+            label_alpha,
+            hover_scale,
+            animation_speed,
+            fade_in_speed,
+            overlay_alpha,
+            wedge_alpha,
+            hover_alpha,
+            default_wedge_alpha,
+            default_hover_alpha,
+            wedge_gap,
+            border_width,
+            border_color,
+            corner_radius,
+            hub_outline,
+            inherit_style,
+            theme
+        )
+    };
+}
+
+/// Appearance and animation settings from the layout's `settings` block.
+///
+/// Every field is optional in the layout file; the defaults reproduce the
+/// original built-in look.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// How menus are laid out, unless a submenu's button chooses otherwise
+    pub layout: LayoutKind,
+    /// Outer ring radius as a fraction of the smaller window dimension
+    pub radius_ratio: f64,
+    /// Outer ring radius as an absolute length, in place of `radius_ratio`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radius: Option<Length>,
+    /// Smallest outer ring radius, however it is sized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_radius: Option<Length>,
+    /// Largest outer ring radius, however it is sized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_radius: Option<Length>,
+    /// Inner (hole) radius as a fraction of the outer radius; sets the ring thickness
+    pub inner_radius_ratio: f64,
+    /// Where the wedges begin, in degrees clockwise from 12 o'clock
+    pub start_angle: f64,
+    /// Angle the ring spans, in degrees; less than 360 for a partial ring
+    pub sweep_angle: f64,
+    /// Direction in which buttons follow each other around the ring
+    pub direction: Direction,
+    /// Point of the window the ring is centered on
+    pub anchor: Anchor,
+    /// How an opened submenu is shown
+    pub submenu_mode: SubmenuMode,
+    /// Number of grid columns; 0 picks a near-square grid
+    pub grid_columns: usize,
+    /// Width and height of a grid cell
+    pub cell_size: Length,
+    /// Space between grid cells and list rows, in pixels
+    pub cell_spacing: f64,
+    /// Width of a list row
+    pub list_width: Length,
+    /// Height of a list row
+    pub row_height: Length,
+    /// Icon glyph size
+    pub icon_size: Length,
+    /// Font family used for icon glyphs
+    pub icon_font: String,
+    /// Font family used for text labels
+    pub label_font: String,
+    /// Label font size
+    pub label_font_size: Length,
+    /// Distance from the icon center down to the label baseline, in pixels
+    pub label_offset: f64,
+    /// Label text opacity
+    pub label_alpha: f64,
+    /// Scale applied to the outer radius and icon of the hovered wedge
+    pub hover_scale: f64,
+    /// Scale change per frame while a wedge grows or shrinks
+    pub animation_speed: f64,
+    /// Progress per frame of the open/submenu fade-in, from 0 to 1
+    pub fade_in_speed: f64,
+    /// Opacity of the dark overlay painted behind the ring
+    pub overlay_alpha: f64,
+    /// Opacity of wedges with a configured color
+    pub wedge_alpha: f64,
+    /// Opacity of hovered wedges with a configured hover color
+    pub hover_alpha: f64,
+    /// Opacity of wedges using the default color
+    pub default_wedge_alpha: f64,
+    /// Opacity of hovered wedges using the default hover color
+    pub default_hover_alpha: f64,
+    /// Angle left empty between neighbouring wedges, in degrees
+    pub wedge_gap: f64,
+    /// Width of the outline drawn around each wedge, in pixels; 0 for none
+    pub border_width: f64,
+    /// Color of the wedge and hub outlines
+    pub border_color: String,
+    /// Radius of the rounded wedge corners, in pixels
+    pub corner_radius: f64,
+    /// Width of the circle drawn around the hub (the ring's hole), in pixels; 0 for none
+    pub hub_outline: f64,
+    /// Whether submenu buttons inherit unset style fields from their parent button
+    pub inherit_style: bool,
+    /// Theme whose named colors buttons can reference as `"@name"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+}
+
+/// The settings a layout file sets, for writing it back out: only the
+/// `keys` written in its `settings` block, whatever their values
+pub struct WrittenSettings<'a> {
+    pub settings: &'a Settings,
+    pub keys: &'a BTreeSet<String>,
+}
+
+impl WrittenSettings<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl Serialize for WrittenSettings<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        macro_rules! write_keys {
+            ($($field:ident),*) => {
+                let Settings { $($field),* } = self.settings;
+                $(
+                    if self.keys.contains(stringify!($field)) {
+                        map.serialize_entry(stringify!($field), $field)?;
+                    }
+                )*
+            };
+        }
+        each_setting!(write_keys);
+        map.end()
+    }
+}
+
+/// A length in logical pixels or in physical millimetres on the monitor,
+/// written as a number of pixels (`240`) or with a unit (`"240px"`, `"40mm"`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawLength", into = "RawLength")]
+pub enum Length {
+    Pixels(f64),
+    Millimetres(f64),
+}
+
+/// A length as written in the layout file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawLength {
+    Number(f64),
+    Text(String),
+}
+
+impl Length {
+    /// The length in logical pixels, on a monitor with `pixels_per_mm`
+    pub fn pixels(self, pixels_per_mm: f64) -> f64 {
+        match self {
+            Length::Pixels(pixels) => pixels,
+            Length::Millimetres(mm) => mm * pixels_per_mm,
+        }
+    }
+
+    fn value(self) -> f64 {
+        match self {
+            Length::Pixels(value) | Length::Millimetres(value) => value,
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let (number, unit): (&str, fn(f64) -> Length) = match text.strip_suffix("mm") {
+            Some(number) => (number, Length::Millimetres),
+            None => (text.strip_suffix("px").unwrap_or(text), Length::Pixels),
+        };
+        number
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .map(unit)
+            .ok_or_else(|| {
+                format!(
+                    "invalid length '{}': expected pixels such as 240 or \"240px\", or millimetres such as \"40mm\"",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Pixels(pixels) => write!(f, "{}px", pixels),
+            Length::Millimetres(mm) => write!(f, "{}mm", mm),
+        }
+    }
+}
+
+impl TryFrom<RawLength> for Length {
+    type Error = String;
+
+    fn try_from(raw: RawLength) -> Result<Self, Self::Error> {
+        match raw {
+            RawLength::Number(pixels) => Ok(Length::Pixels(pixels)),
+            RawLength::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<Length> for RawLength {
+    fn from(length: Length) -> Self {
+        match length {
+            Length::Pixels(pixels) => RawLength::Number(pixels),
+            Length::Millimetres(_) => RawLength::Text(length.to_string()),
+        }
+    }
+}
+
+/// Arrangement of a menu's buttons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutKind {
+    /// Wedges of a ring around the anchor
+    Ring,
+    /// Rows of square cells, wlogout style
+    Grid,
+    /// A single column of rows
+    List,
+}
+
+/// Order of the buttons around the ring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Where in the window the ring's center sits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anchor {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// How submenus open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubmenuMode {
+    /// The submenu takes the place of its parent ring
+    Replace,
+    /// The submenu opens as an outer ring around the parent wedge, with
+    /// the parent ring still shown and usable
+    Expand,
+}
+
+impl Anchor {
+    /// The anchor point in a `width`x`height` window
+    pub fn position(self, width: f64, height: f64) -> (f64, f64) {
+        let (x, y) = self.fractions();
+        (width * x, height * y)
+    }
+
+    /// The anchor point as fractions of the window's width and height; grids
+    /// and lists align the same point of themselves with it
+    pub fn fractions(self) -> (f64, f64) {
+        match self {
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            layout: LayoutKind::Ring,
+            radius_ratio: 0.35,
+            radius: None,
+            min_radius: None,
+            max_radius: None,
+            inner_radius_ratio: 0.3,
+            start_angle: 0.0,
+            sweep_angle: 360.0,
+            direction: Direction::Clockwise,
+            anchor: Anchor::Center,
+            submenu_mode: SubmenuMode::Replace,
+            grid_columns: 0,
+            cell_size: Length::Pixels(160.0),
+            cell_spacing: 16.0,
+            list_width: Length::Pixels(360.0),
+            row_height: Length::Pixels(64.0),
+            icon_size: Length::Pixels(48.0),
+            icon_font: "FiraCode Nerd Font".to_string(),
+            label_font: "Sans".to_string(),
+            label_font_size: Length::Pixels(14.0),
+            label_offset: 40.0,
+            label_alpha: 0.9,
+            hover_scale: 1.12,
+            animation_speed: 0.01,
+            fade_in_speed: 0.12,
+            overlay_alpha: 0.35,
+            wedge_alpha: 0.8,
+            hover_alpha: 0.9,
+            default_wedge_alpha: 0.35,
+            default_hover_alpha: 0.55,
+            wedge_gap: 0.0,
+            border_width: 0.0,
+            border_color: "rgba(0, 0, 0, 0.6)".to_string(),
+            corner_radius: 0.0,
+            hub_outline: 0.0,
+            inherit_style: false,
+            theme: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check value ranges, returning the offending key and a description
+    pub fn validate(&self) -> Result<(), (&'static str, String)> {
+        let numbers = [
+            ("radius_ratio", self.radius_ratio),
+            ("inner_radius_ratio", self.inner_radius_ratio),
+            ("start_angle", self.start_angle),
+            ("sweep_angle", self.sweep_angle),
+            ("cell_spacing", self.cell_spacing),
+            ("label_offset", self.label_offset),
+            ("label_alpha", self.label_alpha),
+            ("hover_scale", self.hover_scale),
+            ("animation_speed", self.animation_speed),
+            ("fade_in_speed", self.fade_in_speed),
+            ("overlay_alpha", self.overlay_alpha),
+            ("wedge_alpha", self.wedge_alpha),
+            ("hover_alpha", self.hover_alpha),
+            ("default_wedge_alpha", self.default_wedge_alpha),
+            ("default_hover_alpha", self.default_hover_alpha),
+            ("wedge_gap", self.wedge_gap),
+            ("border_width", self.border_width),
+            ("corner_radius", self.corner_radius),
+            ("hub_outline", self.hub_outline),
+        ];
+        for (name, value) in numbers {
+            if !value.is_finite() {
+                return Err((name, format!("must be a finite number, got {}", value)));
+            }
+        }
+
+        // A ratio of 1 would leave the ring with no thickness
+        if !(0.0..1.0).contains(&self.inner_radius_ratio) {
+            return Err((
+                "inner_radius_ratio",
+                format!(
+                    "must be at least 0 and below 1, got {}",
+                    self.inner_radius_ratio
+                ),
+            ));
+        }
+
+        let fractions = [
+            ("radius_ratio", self.radius_ratio),
+            ("label_alpha", self.label_alpha),
+            ("overlay_alpha", self.overlay_alpha),
+            ("wedge_alpha", self.wedge_alpha),
+            ("hover_alpha", self.hover_alpha),
+            ("default_wedge_alpha", self.default_wedge_alpha),
+            ("default_hover_alpha", self.default_hover_alpha),
+        ];
+        for (name, value) in fractions {
+            if !(0.0..=1.0).contains(&value) {
+                return Err((name, format!("must be between 0 and 1, got {}", value)));
+            }
+        }
+
+        let positive = [
+            ("hover_scale", self.hover_scale),
+            ("animation_speed", self.animation_speed),
+            ("fade_in_speed", self.fade_in_speed),
+        ];
+        for (name, value) in positive {
+            if value <= 0.0 {
+                return Err((name, format!("must be greater than 0, got {}", value)));
+            }
+        }
+
+        let non_negative = [
+            ("border_width", self.border_width),
+            ("corner_radius", self.corner_radius),
+            ("hub_outline", self.hub_outline),
+            ("cell_spacing", self.cell_spacing),
+        ];
+        for (name, value) in non_negative {
+            if value < 0.0 {
+                return Err((name, format!("must not be negative, got {}", value)));
+            }
+        }
+
+        let lengths = [
+            ("icon_size", Some(self.icon_size)),
+            ("label_font_size", Some(self.label_font_size)),
+            ("cell_size", Some(self.cell_size)),
+            ("list_width", Some(self.list_width)),
+            ("row_height", Some(self.row_height)),
+            ("radius", self.radius),
+            ("min_radius", self.min_radius),
+            ("max_radius", self.max_radius),
+        ];
+        for (name, length) in lengths {
+            let Some(length) = length else { continue };
+            if !length.value().is_finite() {
+                return Err((name, format!("must be a finite length, got {}", length)));
+            }
+            if length.value() <= 0.0 {
+                return Err((name, format!("must be greater than 0, got {}", length)));
+            }
+        }
+        if let (Some(min @ Length::Pixels(_)), Some(max @ Length::Pixels(_)))
+        | (Some(min @ Length::Millimetres(_)), Some(max @ Length::Millimetres(_))) =
+            (self.min_radius, self.max_radius)
+        {
+            if min.value() > max.value() {
+                return Err((
+                    "min_radius",
+                    format!(
+                        "must not be larger than max_radius, got {} and {}",
+                        min, max
+                    ),
+                ));
+            }
+        }
+
+        if !(self.sweep_angle > 0.0 && self.sweep_angle <= 360.0) {
+            return Err((
+                "sweep_angle",
+                format!(
+                    "must be greater than 0 and at most 360, got {}",
+                    self.sweep_angle
+                ),
+            ));
+        }
+
+        if !(0.0..90.0).contains(&self.wedge_gap) {
+            return Err((
+                "wedge_gap",
+                format!(
+                    "must be at least 0 and under 90 degrees, got {}",
+                    self.wedge_gap
+                ),
+            ));
+        }
+
+        if let Err(reason) = parse_color(&self.border_color) {
+            return Err((
+                "border_color",
+                format!("'{}' is not a valid color: {}", self.border_color, reason),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// These settings with the `keys` of `overlay` taken from it
+    pub fn overlay(&self, overlay: &Settings, keys: &BTreeSet<String>) -> Settings {
+        let mut merged = self.clone();
+        macro_rules! take_keys {
+            ($($field:ident),*) => {
+                let Settings { $($field),* } = overlay;
+                $(
+                    if keys.contains(stringify!($field)) {
+                        merged.$field = $field.clone();
+                    }
+                )*
+            };
+        }
+        each_setting!(take_keys);
+        merged
+    }
+
+    /// Outer radius of the innermost ring in a `width`x`height` window on a
+    /// monitor with `pixels_per_mm`: `radius` if set, otherwise
+    /// `radius_ratio` of the smaller dimension, kept within the limits
+    pub fn ring_radius(&self, width: f64, height: f64, pixels_per_mm: f64) -> f64 {
+        let radius = match self.radius {
+            Some(radius) => radius.pixels(pixels_per_mm),
+            None => width.min(height) * self.radius_ratio,
+        };
+        let radius = match self.max_radius {
+            Some(max) => radius.min(max.pixels(pixels_per_mm)),
+            None => radius,
+        };
+        match self.min_radius {
+            Some(min) => radius.max(min.pixels(pixels_per_mm)),
+            None => radius,
+        }
+    }
+
+    /// The parsed `border_color`, which `validate` has checked
+    pub fn border_color(&self) -> Color {
+        parse_color(&self.border_color).unwrap_or(Color::BLACK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+    use crate::format::LayoutFormat;
+
+    #[test]
+    fn test_ring_radius() {
+        let layout = parse_config(
+            r#"{"settings": {"radius": "45mm", "min_radius": 120, "max_radius": "600px"}, "buttons": []}"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        let settings = &layout.settings;
+        assert_eq!(settings.radius, Some(Length::Millimetres(45.0)));
+        assert_eq!(settings.min_radius, Some(Length::Pixels(120.0)));
+        assert_eq!(settings.max_radius, Some(Length::Pixels(600.0)));
+        assert_eq!(settings.ring_radius(1920.0, 1080.0, 4.0), 180.0);
+        assert_eq!(settings.ring_radius(1920.0, 1080.0, 2.0), 120.0);
+        assert_eq!(settings.ring_radius(1920.0, 1080.0, 20.0), 600.0);
+
+        // Without a radius the ratio still applies, within the limits
+        let settings = Settings {
+            max_radius: Some(Length::Millimetres(50.0)),
+            ..Settings::default()
+        };
+        assert_eq!(settings.ring_radius(1000.0, 800.0, 10.0), 280.0);
+        assert_eq!(settings.ring_radius(4000.0, 3000.0, 10.0), 500.0);
+
+        let err = parse_config(
+            r#"{"settings": {"radius": "4cm"}, "buttons": []}"#,
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid length '4cm'"), "{}", err);
+
+        let err = parse_config(
+            r#"{"settings": {"min_radius": "60mm", "max_radius": "50mm"}, "buttons": []}"#,
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:34: settings.min_radius must not be larger than max_radius, got 60mm and 50mm"
+        );
+    }
+
+    #[test]
+    fn test_lengths() {
+        let layout = parse_config(
+            r#"{"settings": {"icon_size": "8mm", "label_font_size": "14px", "row_height": 64},
+                "buttons": [{"label": "lock", "action": "hyprlock", "icon_size": "10mm"}]}"#,
+            "<test>",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        let settings = &layout.settings;
+        assert_eq!(settings.icon_size, Length::Millimetres(8.0));
+        assert_eq!(settings.label_font_size, Length::Pixels(14.0));
+        assert_eq!(settings.row_height, Length::Pixels(64.0));
+        assert_eq!(settings.icon_size.pixels(4.0), 32.0);
+        assert_eq!(layout.buttons[0].icon_size, Some(Length::Millimetres(10.0)));
+
+        let err = parse_config(
+            r#"{"settings": {"cell_size": "0mm"}, "buttons": []}"#,
+            "layout",
+            LayoutFormat::Json,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:1:32: settings.cell_size must be greater than 0, got 0mm"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Settings::default().validate().is_ok());
+
+        let settings = Settings {
+            hover_scale: f64::NAN,
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err((
+                "hover_scale",
+                "must be a finite number, got NaN".to_string()
+            ))
+        );
+
+        let settings = Settings {
+            border_width: f64::INFINITY,
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err((
+                "border_width",
+                "must be a finite number, got inf".to_string()
+            ))
+        );
+
+        let settings = Settings {
+            icon_size: Length::Pixels(f64::NAN),
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err((
+                "icon_size",
+                "must be a finite length, got NaNpx".to_string()
+            ))
+        );
+
+        let settings = Settings {
+            inner_radius_ratio: 1.0,
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err((
+                "inner_radius_ratio",
+                "must be at least 0 and below 1, got 1".to_string()
+            ))
+        );
+
+        let err = parse_config(
+            "settings:\n  hover_scale: .nan\nbuttons: []\n",
+            "layout",
+            LayoutFormat::Yaml,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "layout:2:16: settings.hover_scale must be a finite number, got NaN"
+        );
+    }
+
+    #[test]
+    fn test_overlay() {
+        let base = Settings {
+            radius_ratio: 0.25,
+            radius: Some(Length::Millimetres(40.0)),
+            icon_size: Length::Pixels(40.0),
+            theme: Some("nord".to_string()),
+            ..Settings::default()
+        };
+        let overlay = Settings {
+            icon_size: Length::Millimetres(10.0),
+            wedge_gap: 2.0,
+            ..Settings::default()
+        };
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect();
+
+        // Only the listed keys are taken, even where the overlay holds a default
+        let merged = base.overlay(&overlay, &keys(&["icon_size", "radius_ratio"]));
+        assert_eq!(merged.icon_size, Length::Millimetres(10.0));
+        assert_eq!(merged.radius_ratio, Settings::default().radius_ratio);
+        assert_eq!(merged.wedge_gap, 0.0);
+        assert_eq!(merged.radius, Some(Length::Millimetres(40.0)));
+
+        // A listed optional setting the overlay leaves unset clears the base's
+        let merged = base.overlay(&overlay, &keys(&["radius", "theme"]));
+        assert_eq!(merged.radius, None);
+        assert_eq!(merged.theme, None);
+        assert_eq!(merged.icon_size, Length::Pixels(40.0));
+
+        assert_eq!(base.overlay(&overlay, &BTreeSet::new()), base);
+    }
+}
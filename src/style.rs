@@ -0,0 +1,201 @@
+//! Style defaults and inheritance.
+//!
+//! Each menu level may carry a `defaults` block (the layout's top-level
+//! `defaults` for the root menu, a button's `defaults` for its submenu), and
+//! with `settings.inherit_style` enabled, children also pick up their parent
+//! button's resolved style. [`apply_styles`] bakes both into the buttons
+//! themselves, so drawing only has to look at each button's own fields.
+
+use crate::config::{Button, Layout};
+use crate::settings::Length;
+use serde::{Deserialize, Serialize};
+
+/// The inheritable subset of a button's appearance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Style {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
+    /// Label font family
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_font: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_size: Option<Length>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_label: Option<bool>,
+}
+
+impl Style {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The color fields with their keys in the layout file
+    pub fn colors(&self) -> [(&'static str, &Option<String>); 3] {
+        [
+            ("color", &self.color),
+            ("hover_color", &self.hover_color),
+            ("foreground", &self.foreground),
+        ]
+    }
+
+    /// The style set directly on `button`
+    pub fn of(button: &Button) -> Self {
+        Self {
+            color: button.color.clone(),
+            hover_color: button.hover_color.clone(),
+            foreground: button.foreground.clone(),
+            font: button.font.clone(),
+            icon_font: button.icon_font.clone(),
+            icon_size: button.icon_size,
+            show_label: button.show_label,
+        }
+    }
+
+    /// Fill every unset field from `fallback`.
+    ///
+    /// `color` and `hover_color` go together: a style with its own color
+    /// keeps its own hover color, or none so that one is derived from the
+    /// color, rather than taking a hover color meant for another color.
+    pub fn or(self, fallback: &Style) -> Self {
+        let (color, hover_color) = match self.color {
+            Some(color) => (Some(color), self.hover_color),
+            None => (
+                fallback.color.clone(),
+                self.hover_color.or_else(|| fallback.hover_color.clone()),
+            ),
+        };
+        Self {
+            color,
+            hover_color,
+            foreground: self.foreground.or_else(|| fallback.foreground.clone()),
+            font: self.font.or_else(|| fallback.font.clone()),
+            icon_font: self.icon_font.or_else(|| fallback.icon_font.clone()),
+            icon_size: self.icon_size.or(fallback.icon_size),
+            show_label: self.show_label.or(fallback.show_label),
+        }
+    }
+
+    fn apply_to(self, button: &mut Button) {
+        button.color = self.color;
+        button.hover_color = self.hover_color;
+        button.foreground = self.foreground;
+        button.font = self.font;
+        button.icon_font = self.icon_font;
+        button.icon_size = self.icon_size;
+        button.show_label = self.show_label;
+    }
+}
+
+/// Bake the layout's style defaults into its buttons with [`apply_styles`]
+pub fn style_layout(layout: &mut Layout) {
+    let defaults = std::mem::take(&mut layout.defaults);
+    apply_styles(
+        &mut layout.buttons,
+        &defaults,
+        &Style::default(),
+        layout.settings.inherit_style,
+    );
+}
+
+/// Resolve the style of every button below `buttons`.
+///
+/// A button's own fields win over its level's `defaults`, which win over
+/// the parent button's resolved style when `inherit` is set. The `defaults`
+/// blocks are consumed in the process.
+pub fn apply_styles(buttons: &mut [Button], defaults: &Style, parent: &Style, inherit: bool) {
+    for button in buttons {
+        let style = Style::of(button).or(defaults).or(parent);
+        let child_defaults = button.defaults.take().unwrap_or_default();
+        let child_parent = if inherit {
+            style.clone()
+        } else {
+            Style::default()
+        };
+
+        style.apply_to(button);
+        apply_styles(
+            &mut button.children,
+            &child_defaults,
+            &child_parent,
+            inherit,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+    use crate::format::LayoutFormat;
+
+    fn styled(content: &str, inherit: bool) -> Vec<Button> {
+        let layout = parse_config(content, "layout", LayoutFormat::Json).unwrap();
+        let mut buttons = layout.buttons;
+        apply_styles(&mut buttons, &layout.defaults, &Style::default(), inherit);
+        buttons
+    }
+
+    const LAYOUT: &str = r##"{
+        "defaults": {"color": "#112233", "show_label": true},
+        "buttons": [
+            {"label": "power", "hover_color": "#445566", "font": "Serif",
+             "defaults": {"icon_size": 32},
+             "children": [
+                {"label": "reboot", "action": "systemctl reboot", "show_label": false}
+             ]},
+            {"label": "lock", "action": "hyprlock", "color": "#778899"}
+        ]
+    }"##;
+
+    #[test]
+    fn test_level_defaults() {
+        let buttons = styled(LAYOUT, false);
+        assert_eq!(buttons[0].color.as_deref(), Some("#112233"));
+        assert_eq!(buttons[0].show_label, Some(true));
+        assert_eq!(buttons[0].defaults, None);
+        assert_eq!(buttons[1].color.as_deref(), Some("#778899"));
+
+        // Without inheritance, children only get their own level's defaults
+        let reboot = &buttons[0].children[0];
+        assert_eq!(reboot.icon_size, Some(Length::Pixels(32.0)));
+        assert_eq!(reboot.color, None);
+        assert_eq!(reboot.font, None);
+    }
+
+    #[test]
+    fn test_inherit_style() {
+        let buttons = styled(LAYOUT, true);
+        let reboot = &buttons[0].children[0];
+        assert_eq!(reboot.color.as_deref(), Some("#112233"));
+        assert_eq!(reboot.hover_color.as_deref(), Some("#445566"));
+        assert_eq!(reboot.font.as_deref(), Some("Serif"));
+        assert_eq!(reboot.icon_size, Some(Length::Pixels(32.0)));
+        assert_eq!(reboot.show_label, Some(false));
+    }
+
+    #[test]
+    fn test_inherit_own_color() {
+        // A child with its own color does not hover to the parent's hover
+        // color, which was chosen for the parent's color
+        let buttons = styled(
+            r##"[{"label": "power", "hover_color": "#445566", "children": [
+                {"label": "off", "action": "poweroff", "color": "#ff0000"},
+                {"label": "reboot", "action": "reboot"}
+            ]}]"##,
+            true,
+        );
+        let off = &buttons[0].children[0];
+        assert_eq!(off.color.as_deref(), Some("#ff0000"));
+        assert_eq!(off.hover_color, None);
+
+        let reboot = &buttons[0].children[1];
+        assert_eq!(reboot.hover_color.as_deref(), Some("#445566"));
+    }
+}
@@ -0,0 +1,159 @@
+//! Named color palettes.
+//!
+//! A theme file maps color names to colors, e.g. `{ "red": "#BF616A" }`, in
+//! any of the layout formats. Buttons then write `"color": "@red"` and the
+//! theme can be swapped without touching the layout. A pywal `colors.json`
+//! is also accepted, providing `color0` to `color15`, `background`,
+//! `foreground` and `cursor`.
+
+use crate::color::parse_color;
+use crate::config::{read_config, Source};
+use crate::error::ConfigError;
+use crate::format::{LayoutFormat, Step};
+use crate::paths;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Color names and their values, from one theme file
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    /// Theme file the colors came from, for error messages
+    pub origin: String,
+    colors: BTreeMap<String, String>,
+}
+
+/// On-disk shape of a theme file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThemeFile {
+    Pywal {
+        special: BTreeMap<String, String>,
+        colors: BTreeMap<String, String>,
+    },
+    Flat(BTreeMap<String, String>),
+}
+
+impl Palette {
+    /// Look up a color by name (without the leading `@`)
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.colors.get(name).map(String::as_str)
+    }
+
+    /// Parse a theme file, checking that every entry is a valid color
+    pub fn parse(content: &str, origin: &str, format: LayoutFormat) -> Result<Self, ConfigError> {
+        // Each color with the table it is written in, if any
+        let tables: Vec<(Option<&str>, BTreeMap<String, String>)> =
+            match format.deserialize(content, origin)? {
+                ThemeFile::Pywal { special, colors } => {
+                    vec![(Some("special"), special), (Some("colors"), colors)]
+                }
+                ThemeFile::Flat(colors) => vec![(None, colors)],
+            };
+
+        let source = Source::new(content, origin, format);
+        for (table, colors) in &tables {
+            for (name, value) in colors {
+                if let Err(reason) = parse_color(value) {
+                    let path: Vec<Step> = table
+                        .iter()
+                        .chain([&name.as_str()])
+                        .map(|key| Step::key(key))
+                        .collect();
+                    return Err(ConfigError::InvalidColor {
+                        location: source.locate(&path),
+                        menu_path: format!("@{}", name),
+                        value: value.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            origin: origin.to_string(),
+            colors: tables.into_iter().flat_map(|(_, colors)| colors).collect(),
+        })
+    }
+}
+
+/// Load the theme `spec`, which is either a path or a theme name.
+///
+/// Paths are resolved relative to `base`. A name is looked up as
+/// `themes/<name>.{json,toml,yaml,yml}` in the configuration directories;
+/// the name `wal` falls back to pywal's generated colors.
+pub fn load_theme(spec: &str, base: &Path) -> Result<Palette, ConfigError> {
+    let path = if is_path(spec) {
+        base.join(spec)
+    } else {
+        find_theme(spec).ok_or_else(|| ConfigError::UnknownTheme {
+            name: spec.to_string(),
+        })?
+    };
+
+    log::info!("Loading theme from {}", path.display());
+    let content = read_config(&path)?;
+    Palette::parse(
+        &content,
+        &path.display().to_string(),
+        LayoutFormat::from_path(&path),
+    )
+}
+
+fn is_path(spec: &str) -> bool {
+    spec.contains('/') || Path::new(spec).extension().is_some()
+}
+
+fn find_theme(name: &str) -> Option<PathBuf> {
+    let names: Vec<String> = ["json", "toml", "yaml", "yml"]
+        .iter()
+        .map(|ext| format!("themes/{}.{}", name, ext))
+        .collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+    paths::find_config_file(&names).or_else(|| match name {
+        "wal" => paths::wal_colors_file(),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_palette() {
+        let palette = Palette::parse(
+            "red = \"#BF616A\"\nblue = \"rgb(94, 129, 172)\"\n",
+            "nord.toml",
+            LayoutFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(palette.get("red"), Some("#BF616A"));
+        assert_eq!(palette.get("green"), None);
+
+        let err =
+            Palette::parse(r##"{"red": "#BF616"}"##, "nord.json", LayoutFormat::Json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "nord.json:1:16: invalid color '#BF616' in @red: expected 3, 4, 6 or 8 hex digits"
+        );
+    }
+
+    #[test]
+    fn test_parse_pywal() {
+        let palette = Palette::parse(
+            r##"{
+                "wallpaper": "/home/user/wall.png",
+                "alpha": "100",
+                "special": {"background": "#1d1f21", "foreground": "#c5c8c6", "cursor": "#c5c8c6"},
+                "colors": {"color0": "#1d1f21", "color1": "#cc6666"}
+            }"##,
+            "colors.json",
+            LayoutFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(palette.get("background"), Some("#1d1f21"));
+        assert_eq!(palette.get("color1"), Some("#cc6666"));
+    }
+}